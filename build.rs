@@ -1,9 +1,82 @@
+#[cfg(feature = "lalrpop")]
 extern crate lalrpop;
 
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+#[path = "src/punycode.rs"]
+mod punycode;
+
+const PUBLIC_SUFFIX_LIST_PATH: &str = "data/public_suffix_list.dat";
+
+const RULE_NORMAL: u8 = 1;
+const RULE_WILDCARD: u8 = 2;
+const RULE_EXCEPTION: u8 = 4;
+
 fn main() {
-    build_grammar()
+    #[cfg(feature = "lalrpop")]
+    build_grammar();
+
+    if env::var_os("CARGO_FEATURE_PSL").is_some() {
+        build_public_suffix_list();
+    }
 }
 
+#[cfg(feature = "lalrpop")]
 fn build_grammar() {
     lalrpop::process_root().unwrap();
 }
+
+fn build_public_suffix_list() {
+    let list = fs::read_to_string(PUBLIC_SUFFIX_LIST_PATH).unwrap();
+
+    // Rule names map to the set of rule kinds they appear in, so that a name that is both
+    // a normal rule and the parent of a wildcard rule takes a single entry.
+    let mut rules = BTreeMap::<String, u8>::new();
+    for line in list.lines() {
+        let rule = match line.split_whitespace().next() {
+            Some(rule) if !rule.starts_with("//") => rule,
+            _ => continue,
+        };
+
+        let (kind, name) = if let Some(name) = rule.strip_prefix("*.") {
+            (RULE_WILDCARD, name)
+        } else if let Some(name) = rule.strip_prefix('!') {
+            (RULE_EXCEPTION, name)
+        } else {
+            (RULE_NORMAL, rule)
+        };
+
+        *rules.entry(to_ascii_rule(name)).or_insert(0) |= kind;
+    }
+
+    let mut table = String::from("static RULES: &[(&str, u8)] = &[\n");
+    for (name, kinds) in rules.iter() {
+        table.push_str(&format!("    ({:?}, {}),\n", name, kinds));
+    }
+    table.push_str("];\n");
+
+    let out_dir = env::var_os("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("public_suffix_list.rs"), table).unwrap();
+}
+
+fn to_ascii_rule(name: &str) -> String {
+    name.split('.')
+        .map(|label| {
+            if label.is_ascii() {
+                label.to_ascii_lowercase()
+            } else {
+                let lowercase = label
+                    .chars()
+                    .flat_map(char::to_lowercase)
+                    .collect::<Vec<char>>();
+                let mut encoded = String::from("xn--");
+                punycode::encode(&lowercase, &mut encoded).unwrap();
+                encoded
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(".")
+}
@@ -1,20 +1,62 @@
-use super::{lalrpop_util, CookieLexer, CookieLexerError, CookieToken};
+use super::cookie_parser::Pair;
+use super::{
+    BuildError, CookieHeaderWriter, DuplicateNameError, FromCookieValue, LimitError, ParseError,
+    ParseLimits, RecoveredCookies, ValueError,
+};
 use std::fmt::{Display, Error as FormatterError, Formatter};
-
-const BASIC_COOKIE_ERROR_DESCRIPTION: &'static str = "Cookie Parsing Error";
-const INTERNAL_ERROR_DESCRIPTION: &'static str = "Internal Error";
-const PARSE_ERROR_DESCRIPTION: &'static str = "Parse Error";
-
-#[allow(dead_code)]
-lalrpop_mod!(cookie_grammar);
-
-#[derive(Debug)]
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+const BASIC_COOKIE_ERROR_DESCRIPTION: &str = "Cookie Parsing Error";
+const BASIC_COOKIE_BUILD_ERROR_DESCRIPTION: &str = "Cookie Building Error";
+const INTERNAL_ERROR_DESCRIPTION: &str = "Internal Error";
+
+/// A cookie parsed from a cookie string.
+///
+/// Besides the name and the value, a cookie records where they were found in the string and
+/// whether the value was quoted, and in which header field it was found when parsed with
+/// `Cookie::parse_many`. Cookies are equal if their names and values are, regardless of
+/// where they were found and of quoting.
+#[derive(Clone, Debug)]
 pub struct Cookie<'a> {
     name: &'a str,
     value: &'a str,
+    raw_value: &'a str,
+    name_start: usize,
+    raw_value_start: usize,
+    crumb: usize,
 }
 
 impl<'a> Cookie<'a> {
+    /// Creates a cookie that was not parsed, whose spans are those of the string `name=value`.
+    pub(crate) fn new(name: &'a str, value: &'a str) -> Cookie<'a> {
+        Cookie {
+            name,
+            value,
+            raw_value: value,
+            name_start: 0,
+            raw_value_start: name.len() + 1,
+            crumb: 0,
+        }
+    }
+
+    pub(crate) fn from_pair(input: &'a str, pair: Pair) -> Cookie<'a> {
+        let raw_value = if pair.is_quoted {
+            pair.value.start - 1..pair.value.end + 1
+        } else {
+            pair.value.clone()
+        };
+
+        Cookie {
+            name: &input[pair.name.clone()],
+            value: &input[pair.value],
+            raw_value: &input[raw_value.clone()],
+            name_start: pair.name.start,
+            raw_value_start: raw_value.start,
+            crumb: 0,
+        }
+    }
+
     /// Parses an [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-4.2.1) compliant cookie string.
     ///
     /// # Examples
@@ -31,14 +73,246 @@ impl<'a> Cookie<'a> {
     /// assert_eq!("value2", parsed_cookies[1].get_value());
     /// ```
     pub fn parse(input: &'a str) -> Result<Vec<Cookie<'a>>, Error> {
-        Ok(cookie_grammar::CookiesParser::new()
-            .parse(CookieLexer::new(input))
-            .map_err(ParseError::from_lalrpop_parse_error_to_error)?
-            .clone_to_vec()
-            .iter()
-            .rev()
-            .map(|tok| tok.with_str(input))
-            .collect::<Result<Vec<Cookie>, Error>>()?)
+        ParseLimits::unlimited().parse(input)
+    }
+
+    /// Parses an [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-4.2.1) compliant
+    /// cookie string lazily, without allocating.
+    ///
+    /// The cookies are yielded as soon as they have been read, so cookies preceding a syntax
+    /// error are yielded before the error. After an error, the iterator yields nothing else.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::Cookie;
+    ///
+    /// let mut cookies = Cookie::iter("cookie1=value1; cookie2=value2");
+    ///
+    /// let cookie1 = cookies.next().unwrap().unwrap();
+    /// assert_eq!("cookie1", cookie1.get_name());
+    /// assert_eq!("value1", cookie1.get_value());
+    ///
+    /// let cookie2 = cookies.next().unwrap().unwrap();
+    /// assert_eq!("cookie2", cookie2.get_name());
+    /// assert_eq!("value2", cookie2.get_value());
+    ///
+    /// assert!(cookies.next().is_none());
+    /// ```
+    pub fn iter(input: &'a str) -> impl Iterator<Item = Result<Cookie<'a>, Error>> {
+        ParseLimits::unlimited().iter(input)
+    }
+
+    /// Parses a cookie header that was split into several header fields, or crumbs, as HTTP/2
+    /// ([RFC 9113](https://www.rfc-editor.org/rfc/rfc9113.html#section-8.2.3)) and HTTP/3 allow.
+    ///
+    /// The crumbs are parsed as one cookie string joined with `"; "`, without joining them. Each
+    /// crumb may hold several cookies, and whitespace around a crumb is accepted, as in a single
+    /// cookie string. Without any crumb, there are no cookies.
+    ///
+    /// The cookies borrow from the crumbs, and `Cookie::get_crumb` tells which crumb a cookie was
+    /// found in. Spans are relative to that crumb, as are the offsets of errors, whose crumb is
+    /// given by `ParseError::crumb`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::{Cookie, Error};
+    ///
+    /// let parsed_cookies = Cookie::parse_many(vec!["a=1; b=2", "c=3"]).unwrap();
+    /// assert_eq!(3, parsed_cookies.len());
+    /// assert_eq!("c", parsed_cookies[2].get_name());
+    /// assert_eq!(1, parsed_cookies[2].get_crumb());
+    /// assert_eq!(2..3, parsed_cookies[2].get_value_span());
+    ///
+    /// let crumbs = ["a=1", "b=x,y"];
+    /// match Cookie::parse_many(crumbs.iter().copied()) {
+    ///     Err(Error::ParseError(err)) => {
+    ///         assert_eq!(1, err.crumb());
+    ///         assert_eq!(3, err.offset());
+    ///         assert_eq!(",", &crumbs[err.crumb()][err.span()]);
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn parse_many<I>(crumbs: I) -> Result<Vec<Cookie<'a>>, Error>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        ParseLimits::unlimited().parse_many(crumbs)
+    }
+
+    /// Finds the first cookie with the given name in an
+    /// [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-4.2.1) compliant cookie
+    /// string, without allocating.
+    ///
+    /// The result is the same as parsing the string with `parse` and taking the first cookie with
+    /// a matching name: names are compared case-sensitively, a pair without a `=` has an empty
+    /// name, and quoted values are unquoted. Because a syntax error anywhere makes `parse` fail,
+    /// the rest of the string is still validated after a match, but no further cookies are
+    /// compared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::Cookie;
+    ///
+    /// let session = Cookie::find("theme=dark; session=\"abc123\"", "session").unwrap();
+    /// assert_eq!("abc123", session.unwrap().get_value());
+    ///
+    /// assert!(Cookie::find("theme=dark", "session").unwrap().is_none());
+    /// assert!(Cookie::find("session=abc123; theme=a,b", "session").is_err());
+    /// ```
+    pub fn find(input: &'a str, name: &str) -> Result<Option<Cookie<'a>>, Error> {
+        ParseLimits::unlimited().find(input, name)
+    }
+
+    /// Finds every cookie with the given name in an
+    /// [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-4.2.1) compliant cookie
+    /// string, in the order they appear.
+    ///
+    /// The result is the same as parsing the string with `parse` and keeping the cookies with a
+    /// matching name, but only the matching cookies are collected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::Cookie;
+    ///
+    /// let ids = Cookie::find_all("id=1; theme=dark; id=2", "id").unwrap();
+    ///
+    /// assert_eq!(2, ids.len());
+    /// assert_eq!("1", ids[0].get_value());
+    /// assert_eq!("2", ids[1].get_value());
+    /// ```
+    pub fn find_all(input: &'a str, name: &str) -> Result<Vec<Cookie<'a>>, Error> {
+        ParseLimits::unlimited().find_all(input, name)
+    }
+
+    /// Parses an [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-4.2.1) compliant
+    /// cookie string given as bytes, such as an HTTP header value that is not valid UTF-8.
+    ///
+    /// Bytes outside the ASCII range are rejected, like in `parse`. Use
+    /// `parse_bytes_with_policy` to accept them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::Cookie;
+    ///
+    /// let parsed_cookies = Cookie::parse_bytes(b"cookie1=value1; cookie2=value2").unwrap();
+    ///
+    /// assert_eq!(b"cookie1", parsed_cookies[0].get_name());
+    /// assert_eq!(b"value1", parsed_cookies[0].get_value());
+    ///
+    /// assert!(Cookie::parse_bytes(b"cookie1=caf\xe9").is_err());
+    /// ```
+    pub fn parse_bytes(input: &'a [u8]) -> Result<Vec<ByteCookie<'a>>, Error> {
+        ParseLimits::unlimited().parse_bytes(input)
+    }
+
+    /// Parses a cookie string given as bytes, handling bytes outside the ASCII range, such as
+    /// obs-text, according to the given `NonAsciiPolicy`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::{Cookie, NonAsciiPolicy};
+    ///
+    /// let input = b"lang=en; name=caf\xe9";
+    /// assert!(Cookie::parse_bytes_with_policy(input, NonAsciiPolicy::Reject).is_err());
+    ///
+    /// let parsed_cookies =
+    ///     Cookie::parse_bytes_with_policy(input, NonAsciiPolicy::AllowInValues).unwrap();
+    ///
+    /// assert_eq!(b"name", parsed_cookies[1].get_name());
+    /// assert_eq!(b"caf\xe9", parsed_cookies[1].get_value());
+    /// ```
+    pub fn parse_bytes_with_policy(
+        input: &'a [u8],
+        non_ascii_policy: NonAsciiPolicy,
+    ) -> Result<Vec<ByteCookie<'a>>, Error> {
+        ParseLimits::unlimited().parse_bytes_with_policy(input, non_ascii_policy)
+    }
+
+    /// Parses a cookie string using the given `ParseMode`.
+    ///
+    /// `ParseMode::Strict` behaves like `parse`. `ParseMode::Lenient` accepts the headers
+    /// browsers send and never fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::{Cookie, ParseMode};
+    ///
+    /// assert!(Cookie::parse_with_mode("a=1;b=hello world;", ParseMode::Strict).is_err());
+    ///
+    /// let parsed_cookies = Cookie::parse_with_mode("a=1;b=hello world;", ParseMode::Lenient).unwrap();
+    ///
+    /// assert_eq!("a", parsed_cookies[0].get_name());
+    /// assert_eq!("1", parsed_cookies[0].get_value());
+    ///
+    /// assert_eq!("b", parsed_cookies[1].get_name());
+    /// assert_eq!("hello world", parsed_cookies[1].get_value());
+    /// ```
+    pub fn parse_with_mode(input: &'a str, mode: ParseMode) -> Result<Vec<Cookie<'a>>, Error> {
+        ParseLimits::unlimited().parse_with_mode(input, mode)
+    }
+
+    /// Parses a cookie string, skipping malformed cookie pairs instead of failing.
+    ///
+    /// The string is split at every `;`, and each segment, without surrounding whitespace, is
+    /// parsed as a single cookie pair of the strict grammar. Segments that are empty or malformed
+    /// are reported with their byte range and the reason they were skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::Cookie;
+    ///
+    /// let recovered = Cookie::parse_recovering("cookie1=value1; bad=a,b; cookie2=value2");
+    ///
+    /// assert_eq!("cookie1", recovered.get_cookies()[0].get_name());
+    /// assert_eq!("cookie2", recovered.get_cookies()[1].get_name());
+    ///
+    /// assert_eq!(16..23, recovered.get_skipped()[0].get_range());
+    /// ```
+    pub fn parse_recovering(input: &'a str) -> RecoveredCookies<'a> {
+        match ParseLimits::unlimited().parse_recovering(input) {
+            Ok(recovered) => recovered,
+            Err(_) => unreachable!("unlimited parse limits cannot be exceeded"),
+        }
+    }
+
+    /// Serializes cookies into a `Cookie` header value that `parse` reads back with the same
+    /// names and values, using a `CookieHeaderWriter`.
+    ///
+    /// Fails if a name is neither empty nor a token, or if a value contains characters other
+    /// than cookie-octets and `=`. Values are never quoted. No cookies serialize to an empty
+    /// string, which is not a valid header value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::Cookie;
+    ///
+    /// let cookies = Cookie::parse("SID=\"31d4d96e407aad42\"; lang=en-US").unwrap();
+    /// let header = Cookie::serialize(&cookies).unwrap();
+    ///
+    /// assert_eq!("SID=31d4d96e407aad42; lang=en-US", header);
+    /// assert_eq!(cookies, Cookie::parse(&header).unwrap());
+    /// ```
+    pub fn serialize<'b, I>(cookies: I) -> Result<String, Error>
+    where
+        'a: 'b,
+        I: IntoIterator<Item = &'b Cookie<'a>>,
+    {
+        let mut writer = CookieHeaderWriter::new(String::new());
+        for cookie in cookies {
+            writer.write_cookie(cookie)?;
+        }
+
+        Ok(writer.into_inner())
     }
 
     /// Gets the name of the cookie.
@@ -68,197 +342,440 @@ impl<'a> Cookie<'a> {
     pub fn get_value(&self) -> &'a str {
         self.value
     }
+
+    /// Decodes the value of the cookie with `FromCookieValue`. If the value cannot be decoded,
+    /// the `Error::ValueError` names the cookie and the type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::{Cookie, Error};
+    ///
+    /// let parsed_cookies = Cookie::parse("visits=12; admin=yes").unwrap();
+    /// assert_eq!(12, parsed_cookies[0].parse_value::<u32>().unwrap());
+    ///
+    /// match parsed_cookies[1].parse_value::<bool>() {
+    ///     Err(Error::ValueError(err)) => {
+    ///         assert_eq!("admin", err.name());
+    ///         assert_eq!("bool", err.type_name());
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn parse_value<T: FromCookieValue>(&self) -> Result<T, Error> {
+        T::from_cookie_value(self.value)
+            .map_err(|err| ValueError::new::<T>(self.name, err).into_error())
+    }
+
+    /// Gets the byte range of the name in the parsed string. Cookies without a name have an
+    /// empty range at the start of the pair.
+    ///
+    /// Cookies that were not parsed, such as those of a `CookieJar`, have the spans of the
+    /// string `name=value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::Cookie;
+    ///
+    /// let parsed_cookies = Cookie::parse("a=1; name=\"value\"").unwrap();
+    /// assert_eq!(5..9, parsed_cookies[1].get_name_span());
+    /// ```
+    pub fn get_name_span(&self) -> Range<usize> {
+        self.name_start..self.name_start + self.name.len()
+    }
+
+    /// Gets the byte range of the value in the parsed string, including the double quotes of a
+    /// quoted value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::Cookie;
+    ///
+    /// let parsed_cookies = Cookie::parse("a=1; name=\"value\"").unwrap();
+    /// assert_eq!(10..17, parsed_cookies[1].get_value_span());
+    /// ```
+    pub fn get_value_span(&self) -> Range<usize> {
+        self.raw_value_start..self.raw_value_start + self.raw_value.len()
+    }
+
+    /// Gets the value as it appeared in the parsed string, including the double quotes of a
+    /// quoted value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::Cookie;
+    ///
+    /// let parsed_cookies = Cookie::parse("name=\"value\"").unwrap();
+    /// assert_eq!("value", parsed_cookies[0].get_value());
+    /// assert_eq!("\"value\"", parsed_cookies[0].get_raw_value());
+    /// ```
+    pub fn get_raw_value(&self) -> &'a str {
+        self.raw_value
+    }
+
+    /// Determines whether the value was enclosed in double quotes in the parsed string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::Cookie;
+    ///
+    /// let parsed_cookies = Cookie::parse("a=\"x\"; b=x").unwrap();
+    /// assert!(parsed_cookies[0].was_quoted());
+    /// assert!(!parsed_cookies[1].was_quoted());
+    /// ```
+    pub fn was_quoted(&self) -> bool {
+        // Unquoting is the only difference between the raw value and the value.
+        self.raw_value.len() != self.value.len()
+    }
+
+    /// Gets the index of the header field the cookie was found in, when parsed with
+    /// `Cookie::parse_many`. The spans of the cookie are relative to that header field.
+    ///
+    /// Cookies parsed from a single cookie string are always in header field 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::Cookie;
+    ///
+    /// let parsed_cookies = Cookie::parse_many(vec!["a=1", "b=2; c=3"]).unwrap();
+    /// assert_eq!(1, parsed_cookies[2].get_crumb());
+    /// assert_eq!(5..6, parsed_cookies[2].get_name_span());
+    /// ```
+    pub fn get_crumb(&self) -> usize {
+        self.crumb
+    }
+
+    pub(crate) fn in_crumb(mut self, crumb: usize) -> Cookie<'a> {
+        self.crumb = crumb;
+        self
+    }
+
+    /// Copies the name and the value of the cookie into a `CookieBuf`, which does not borrow
+    /// from the parsed string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::{Cookie, CookieBuf};
+    ///
+    /// let cookie: CookieBuf = {
+    ///     let input = String::from("name=value");
+    ///     Cookie::parse(&input).unwrap().remove(0).into_owned()
+    /// };
+    ///
+    /// assert_eq!("name", cookie.get_name());
+    /// assert_eq!("value", cookie.get_value());
+    /// ```
+    pub fn into_owned(self) -> CookieBuf {
+        CookieBuf {
+            name: self.name.to_owned(),
+            raw_value: self.raw_value.to_owned(),
+            is_quoted: self.was_quoted(),
+            name_start: self.name_start,
+            raw_value_start: self.raw_value_start,
+            crumb: self.crumb,
+        }
+    }
 }
 
-#[derive(Debug)]
-pub enum Error {
-    InternalError(InternalError),
-    ParseError(ParseError),
+impl<'a> PartialEq for Cookie<'a> {
+    fn eq(&self, other: &Cookie<'a>) -> bool {
+        self.name == other.name && self.value == other.value
+    }
 }
 
-impl Display for Error {
-    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
-        f.write_str(BASIC_COOKIE_ERROR_DESCRIPTION)?;
-        f.write_str(": ")?;
-        match self {
-            Error::InternalError(err) => err.fmt(f),
-            Error::ParseError(err) => err.fmt(f),
-        }
+impl<'a> Eq for Cookie<'a> {}
+
+impl<'a> Hash for Cookie<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.value.hash(state);
     }
 }
 
-impl std::error::Error for Error {
-    fn description(&self) -> &str {
-        BASIC_COOKIE_ERROR_DESCRIPTION
+impl<'a> PartialEq<CookieBuf> for Cookie<'a> {
+    fn eq(&self, other: &CookieBuf) -> bool {
+        *self == other.as_cookie()
     }
+}
 
-    fn cause(&self) -> Option<&dyn std::error::Error> {
-        self.source()
+/// An owned cookie, created with `Cookie::into_owned`, which can outlive the string it was
+/// parsed from.
+///
+/// Like `Cookie`, cookies are equal if their names and values are.
+#[derive(Clone, Debug)]
+pub struct CookieBuf {
+    name: String,
+    raw_value: String,
+    is_quoted: bool,
+    name_start: usize,
+    raw_value_start: usize,
+    crumb: usize,
+}
+
+impl CookieBuf {
+    /// Gets the name of the cookie.
+    pub fn get_name(&self) -> &str {
+        &self.name
     }
 
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        match self {
-            Error::InternalError(err) => Some(err),
-            Error::ParseError(err) => Some(err),
+    /// Gets the value of the cookie.
+    pub fn get_value(&self) -> &str {
+        if self.is_quoted {
+            &self.raw_value[1..self.raw_value.len() - 1]
+        } else {
+            &self.raw_value
+        }
+    }
+
+    /// Borrows the cookie as a `Cookie`, with the spans, quoting and header field of the parsed
+    /// cookie.
+    pub fn as_cookie(&self) -> Cookie<'_> {
+        Cookie {
+            name: &self.name,
+            value: self.get_value(),
+            raw_value: &self.raw_value,
+            name_start: self.name_start,
+            raw_value_start: self.raw_value_start,
+            crumb: self.crumb,
         }
     }
 }
 
-#[derive(Debug)]
-pub struct InternalError(InternalErrorKind);
+impl PartialEq for CookieBuf {
+    fn eq(&self, other: &CookieBuf) -> bool {
+        self.as_cookie() == other.as_cookie()
+    }
+}
+
+impl Eq for CookieBuf {}
 
-impl InternalError {
-    pub(crate) fn to_error(self) -> Error {
-        Error::InternalError(self)
+impl Hash for CookieBuf {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_cookie().hash(state);
     }
 }
 
-impl Display for InternalError {
-    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
-        f.write_str(INTERNAL_ERROR_DESCRIPTION)
+impl<'a> From<Cookie<'a>> for CookieBuf {
+    fn from(cookie: Cookie<'a>) -> CookieBuf {
+        cookie.into_owned()
     }
 }
 
-impl std::error::Error for InternalError {
-    fn description(&self) -> &str {
-        INTERNAL_ERROR_DESCRIPTION
+impl<'a> PartialEq<Cookie<'a>> for CookieBuf {
+    fn eq(&self, other: &Cookie<'a>) -> bool {
+        other == self
     }
+}
 
-    fn cause(&self) -> Option<&dyn std::error::Error> {
-        None
+/// A cookie parsed from bytes with `Cookie::parse_bytes`, whose value may not be valid UTF-8.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ByteCookie<'a> {
+    name: &'a [u8],
+    value: &'a [u8],
+}
+
+impl<'a> ByteCookie<'a> {
+    pub(crate) fn from_pair(input: &'a [u8], pair: Pair) -> ByteCookie<'a> {
+        ByteCookie {
+            name: &input[pair.name],
+            value: &input[pair.value],
+        }
     }
 
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        None
+    /// Gets the name of the cookie.
+    pub fn get_name(&self) -> &'a [u8] {
+        self.name
+    }
+
+    /// Gets the value of the cookie.
+    pub fn get_value(&self) -> &'a [u8] {
+        self.value
     }
 }
 
-#[derive(Debug)]
-enum InternalErrorKind {
-    NonTerminalIndexBeyondBoundaries,
+/// Selects how `Cookie::parse_bytes_with_policy` treats bytes outside the ASCII range.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NonAsciiPolicy {
+    /// Rejects the bytes, as [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-4.1.1)
+    /// requires.
+    #[default]
+    Reject,
+    /// Accepts the bytes as cookie-octets, so that they may appear in values, quoted or not, but
+    /// not in names, which have to be tokens.
+    AllowInValues,
 }
 
-type LalrpopError = lalrpop_util::ParseError<usize, CookieToken, CookieLexerError>;
+/// Selects how strictly `Cookie::parse_with_mode` interprets a cookie string.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ParseMode {
+    /// Requires the [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-4.2.1) syntax,
+    /// with pairs separated by exactly `"; "` and values made of cookie-octets.
+    #[default]
+    Strict,
+    /// Follows the
+    /// [rfc6265bis](https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis#section-5.6)
+    /// rules browsers use. Pairs are separated by `;` with optional whitespace, empty pairs are
+    /// skipped, values may contain any character except `;`, and a pair without a `=` is a
+    /// cookie with an empty name.
+    Lenient,
+}
 
+/// An error returned while parsing or building cookies.
+///
+/// New variants may be added as the crate grows, so matches on `Error` need a wildcard arm.
 #[derive(Debug)]
-pub struct ParseError {
-    lalrpop_error: LalrpopError,
+#[non_exhaustive]
+pub enum Error {
+    InternalError(InternalError),
+    ParseError(ParseError),
+    BuildError(BuildError),
+    LimitError(LimitError),
+    DuplicateNameError(DuplicateNameError),
+    ValueError(ValueError),
 }
 
-impl ParseError {
-    pub(crate) fn from_lalrpop_parse_error_to_error(src: LalrpopError) -> Error {
-        ParseError { lalrpop_error: src }.to_error()
+impl Error {
+    /// Renders the input with the location of a parse error underlined, followed by an
+    /// explanation. Errors other than `Error::ParseError` are rendered as their message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::Cookie;
+    ///
+    /// let input = "a=1; b=x,y";
+    /// let err = Cookie::parse(input).unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     "a=1; b=x,y\n        ^\nunexpected character ',' at byte 8, \
+    ///      expected cookie-octet, '=', ';', whitespace or end of input",
+    ///     err.render(input)
+    /// );
+    /// ```
+    pub fn render(&self, input: &str) -> String {
+        match self {
+            Error::ParseError(err) => err.render(input),
+            err => err.to_string(),
+        }
     }
 
-    fn to_error(self) -> Error {
-        Error::ParseError(self)
+    fn description_str(&self) -> &'static str {
+        match self {
+            Error::BuildError(_) => BASIC_COOKIE_BUILD_ERROR_DESCRIPTION,
+            _ => BASIC_COOKIE_ERROR_DESCRIPTION,
+        }
     }
 }
 
-impl Display for ParseError {
+impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
-        f.write_str(PARSE_ERROR_DESCRIPTION)?;
+        f.write_str(self.description_str())?;
         f.write_str(": ")?;
-        self.lalrpop_error.fmt(f)
+        match self {
+            Error::InternalError(err) => err.fmt(f),
+            Error::ParseError(err) => err.fmt(f),
+            Error::BuildError(err) => err.fmt(f),
+            Error::LimitError(err) => err.fmt(f),
+            Error::DuplicateNameError(err) => err.fmt(f),
+            Error::ValueError(err) => err.fmt(f),
+        }
     }
 }
 
-impl std::error::Error for ParseError {
+impl std::error::Error for Error {
     fn description(&self) -> &str {
-        PARSE_ERROR_DESCRIPTION
+        self.description_str()
     }
 
     fn cause(&self) -> Option<&dyn std::error::Error> {
-        Some(&self.lalrpop_error)
+        self.source()
     }
 
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        Some(&self.lalrpop_error)
+        match self {
+            Error::InternalError(err) => Some(err),
+            Error::ParseError(err) => Some(err),
+            Error::BuildError(err) => Some(err),
+            Error::LimitError(err) => Some(err),
+            Error::DuplicateNameError(err) => Some(err),
+            Error::ValueError(err) => Some(err),
+        }
     }
 }
 
-mod terminals {
-    use super::nonterminals::NonTerminalSpan;
-    use super::Cookie as FullyParsedCookie;
-    use super::{Error, InternalError};
+/// An internal error of the generated parser.
+///
+/// Never constructed: the hand-written parser that replaced the generated one has no internal
+/// errors. It remains part of `Error` only because removing a variant is a breaking change.
+#[derive(Debug)]
+pub struct InternalError(InternalErrorKind);
 
-    #[derive(Clone, Debug)]
-    pub struct Cookie {
-        pub(super) key: NonTerminalSpan,
-        pub(super) value: NonTerminalSpan,
+impl Display for InternalError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.write_str(INTERNAL_ERROR_DESCRIPTION)?;
+        f.write_str(": ")?;
+        f.write_str(self.0.as_str())
     }
+}
 
-    impl Cookie {
-        pub(super) fn with_str<'a>(&self, data: &'a str) -> Result<FullyParsedCookie<'a>, Error> {
-            Ok(FullyParsedCookie {
-                name: self.key.as_str(data).map_err(InternalError::to_error)?,
-                value: self.value.as_str(data).map_err(InternalError::to_error)?,
-            })
-        }
+impl std::error::Error for InternalError {
+    fn description(&self) -> &str {
+        INTERNAL_ERROR_DESCRIPTION
     }
-}
 
-mod nonterminals {
-    use super::{InternalError, InternalErrorKind};
+    fn cause(&self) -> Option<&dyn std::error::Error> {
+        None
+    }
 
-    #[derive(Clone, Debug)]
-    pub struct NonTerminalSpan {
-        start: usize,
-        end: usize,
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
     }
+}
 
-    impl NonTerminalSpan {
-        pub(crate) fn new(start: usize, end: usize) -> NonTerminalSpan {
-            NonTerminalSpan {
-                start: start,
-                end: end,
-            }
-        }
+/// Has no values, so an `InternalError` cannot be constructed.
+#[derive(Debug)]
+enum InternalErrorKind {}
 
-        pub(crate) fn as_str<'a>(&self, data: &'a str) -> Result<&'a str, InternalError> {
-            match data.get(self.start..self.end) {
-                Some(res) => Ok(res),
-                None => Err(InternalError(
-                    InternalErrorKind::NonTerminalIndexBeyondBoundaries,
-                )),
-            }
-        }
+impl InternalErrorKind {
+    fn as_str(&self) -> &'static str {
+        match *self {}
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Cookie;
+    use super::{Cookie, CookieBuf, Error, NonAsciiPolicy, ParseMode};
+    use crate::ParseErrorKind;
 
     #[test]
     fn get_name() {
-        const COOKIE_KEY: &'static str = "cookie_key";
-        const COOKIE_VALUE: &'static str = "cookie_value";
+        const COOKIE_KEY: &str = "cookie_key";
+        const COOKIE_VALUE: &str = "cookie_value";
 
-        let cookie = Cookie {
-            name: COOKIE_KEY,
-            value: COOKIE_VALUE,
-        };
+        let cookie = Cookie::new(COOKIE_KEY, COOKIE_VALUE);
 
         assert_eq!(COOKIE_KEY, cookie.get_name());
     }
 
     #[test]
     fn get_value() {
-        const COOKIE_KEY: &'static str = "cookie_key";
-        const COOKIE_VALUE: &'static str = "cookie_value";
+        const COOKIE_KEY: &str = "cookie_key";
+        const COOKIE_VALUE: &str = "cookie_value";
 
-        let cookie = Cookie {
-            name: COOKIE_KEY,
-            value: COOKIE_VALUE,
-        };
+        let cookie = Cookie::new(COOKIE_KEY, COOKIE_VALUE);
 
         assert_eq!(COOKIE_VALUE, cookie.get_value());
     }
 
     #[test]
     fn single_cookie() {
-        const COOKIE_STR: &'static str = "test=1234";
+        const COOKIE_STR: &str = "test=1234";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(1, parsed_cookies.len());
 
@@ -269,7 +786,7 @@ mod tests {
 
     #[test]
     fn single_cookie_quoted() {
-        const COOKIE_STR: &'static str = "quoted_test=\"quotedval\"";
+        const COOKIE_STR: &str = "quoted_test=\"quotedval\"";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(1, parsed_cookies.len());
 
@@ -280,7 +797,7 @@ mod tests {
 
     #[test]
     fn single_cookie_with_equals_in_value() {
-        const COOKIE_STR: &'static str = "test=abc=123";
+        const COOKIE_STR: &str = "test=abc=123";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(1, parsed_cookies.len());
 
@@ -291,7 +808,7 @@ mod tests {
 
     #[test]
     fn single_cookie_ows_before() {
-        const COOKIE_STR: &'static str = " \x09 ztest=9876";
+        const COOKIE_STR: &str = " \x09 ztest=9876";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(1, parsed_cookies.len());
 
@@ -302,7 +819,7 @@ mod tests {
 
     #[test]
     fn single_cookie_ows_with_single_space_before() {
-        const COOKIE_STR: &'static str = " qtest=9878";
+        const COOKIE_STR: &str = " qtest=9878";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(1, parsed_cookies.len());
 
@@ -313,7 +830,7 @@ mod tests {
 
     #[test]
     fn single_cookie_ows_after() {
-        const COOKIE_STR: &'static str = "abcde=77766test \x09\x09    ";
+        const COOKIE_STR: &str = "abcde=77766test \x09\x09    ";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(1, parsed_cookies.len());
 
@@ -324,7 +841,7 @@ mod tests {
 
     #[test]
     fn single_cookie_ows_with_single_space_after() {
-        const COOKIE_STR: &'static str = "xyzzz=test3 ";
+        const COOKIE_STR: &str = "xyzzz=test3 ";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(1, parsed_cookies.len());
 
@@ -335,7 +852,7 @@ mod tests {
 
     #[test]
     fn single_cookie_ows_before_and_after() {
-        const COOKIE_STR: &'static str = " \x09 ztest=9876       ";
+        const COOKIE_STR: &str = " \x09 ztest=9876       ";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(1, parsed_cookies.len());
 
@@ -346,7 +863,7 @@ mod tests {
 
     #[test]
     fn single_cookie_empty_name() {
-        const COOKIE_STR: &'static str = "=nokey";
+        const COOKIE_STR: &str = "=nokey";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(1, parsed_cookies.len());
 
@@ -357,7 +874,7 @@ mod tests {
 
     #[test]
     fn single_cookie_empty_name_with_ows_before() {
-        const COOKIE_STR: &'static str = " =nokey";
+        const COOKIE_STR: &str = " =nokey";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(1, parsed_cookies.len());
 
@@ -368,7 +885,7 @@ mod tests {
 
     #[test]
     fn single_cookie_empty_value() {
-        const COOKIE_STR: &'static str = "noval=";
+        const COOKIE_STR: &str = "noval=";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(1, parsed_cookies.len());
 
@@ -379,7 +896,7 @@ mod tests {
 
     #[test]
     fn single_cookie_empty_value_with_ows_after() {
-        const COOKIE_STR: &'static str = "noval= ";
+        const COOKIE_STR: &str = "noval= ";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(1, parsed_cookies.len());
 
@@ -390,7 +907,7 @@ mod tests {
 
     #[test]
     fn single_cookie_empty_name_and_val() {
-        const COOKIE_STR: &'static str = "=";
+        const COOKIE_STR: &str = "=";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(1, parsed_cookies.len());
 
@@ -401,7 +918,7 @@ mod tests {
 
     #[test]
     fn single_cookie_empty_name_no_equals() {
-        const COOKIE_STR: &'static str = "nokey";
+        const COOKIE_STR: &str = "nokey";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(1, parsed_cookies.len());
 
@@ -412,7 +929,7 @@ mod tests {
 
     #[test]
     fn two_cookies() {
-        const COOKIE_STR: &'static str = "test1=01234; test2=testval";
+        const COOKIE_STR: &str = "test1=01234; test2=testval";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(2, parsed_cookies.len());
 
@@ -427,7 +944,7 @@ mod tests {
 
     #[test]
     fn three_cookies() {
-        const COOKIE_STR: &'static str = "test1=0x1234; test2=test2; third_val=v4lue";
+        const COOKIE_STR: &str = "test1=0x1234; test2=test2; third_val=v4lue";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(3, parsed_cookies.len());
 
@@ -446,7 +963,7 @@ mod tests {
 
     #[test]
     fn three_cookies_ows_before() {
-        const COOKIE_STR: &'static str = " test1=0x1234; test2=test2; third_val=v4lue";
+        const COOKIE_STR: &str = " test1=0x1234; test2=test2; third_val=v4lue";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(3, parsed_cookies.len());
 
@@ -465,7 +982,7 @@ mod tests {
 
     #[test]
     fn three_cookies_ows_after() {
-        const COOKIE_STR: &'static str = "test1=0x1234; test2=test2; third_val=v4lue   ";
+        const COOKIE_STR: &str = "test1=0x1234; test2=test2; third_val=v4lue   ";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(3, parsed_cookies.len());
 
@@ -484,7 +1001,7 @@ mod tests {
 
     #[test]
     fn three_cookies_ows_before_and_after() {
-        const COOKIE_STR: &'static str = "   test1=0x1234; test2=test2; third_val=v4lue ";
+        const COOKIE_STR: &str = "   test1=0x1234; test2=test2; third_val=v4lue ";
         let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
         assert_eq!(3, parsed_cookies.len());
 
@@ -500,4 +1017,464 @@ mod tests {
         assert_eq!("third_val", parsed_cookie_2.name);
         assert_eq!("v4lue", parsed_cookie_2.value);
     }
+
+    #[test]
+    fn lenient_mode() {
+        const COOKIE_STR: &str = "test1=0x1234;test2=two words;  ; third_val=\"v4lue\";";
+        assert!(Cookie::parse_with_mode(COOKIE_STR, ParseMode::Strict).is_err());
+
+        let parsed_cookies = Cookie::parse_with_mode(COOKIE_STR, ParseMode::Lenient).unwrap();
+        assert_eq!(3, parsed_cookies.len());
+
+        let parsed_cookie_0 = &parsed_cookies[0];
+        assert_eq!("test1", parsed_cookie_0.name);
+        assert_eq!("0x1234", parsed_cookie_0.value);
+
+        let parsed_cookie_1 = &parsed_cookies[1];
+        assert_eq!("test2", parsed_cookie_1.name);
+        assert_eq!("two words", parsed_cookie_1.value);
+
+        let parsed_cookie_2 = &parsed_cookies[2];
+        assert_eq!("third_val", parsed_cookie_2.name);
+        assert_eq!("v4lue", parsed_cookie_2.value);
+    }
+
+    #[test]
+    fn strict_mode() {
+        const COOKIE_STR: &str = "test1=01234; test2=testval";
+        let parsed_cookies = Cookie::parse_with_mode(COOKIE_STR, ParseMode::Strict).unwrap();
+        assert_eq!(2, parsed_cookies.len());
+        assert_eq!("test2", parsed_cookies[1].name);
+        assert_eq!("testval", parsed_cookies[1].value);
+    }
+
+    fn parse_error(input: &str) -> (ParseErrorKind, usize) {
+        match Cookie::parse(input) {
+            Err(Error::ParseError(err)) => (err.kind(), err.offset()),
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn invalid_character_not_truncated() {
+        assert_eq!(
+            (ParseErrorKind::UnexpectedCharacter, 3),
+            parse_error("a=b,c")
+        );
+        assert_eq!(
+            (ParseErrorKind::UnexpectedCharacter, 13),
+            parse_error("test1=0x12; tést2=1")
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!((ParseErrorKind::UnexpectedEnd, 0), parse_error(""));
+        assert_eq!((ParseErrorKind::UnexpectedEnd, 9), parse_error("test1=01;"));
+        assert_eq!(
+            (ParseErrorKind::MissingSeparator, 9),
+            parse_error("test1=01;test2=02")
+        );
+        assert_eq!(
+            (ParseErrorKind::UnterminatedQuote, 9),
+            parse_error("test1=\"01")
+        );
+    }
+
+    #[test]
+    fn iter() {
+        const COOKIE_STR: &str = " test1=0x1234; =\"test2\"; \"nokey\"; test4= ";
+        let cookies = Cookie::iter(COOKIE_STR)
+            .map(|cookie| cookie.map(|c| (c.name, c.value)))
+            .collect::<Result<Vec<(&str, &str)>, Error>>()
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                ("test1", "0x1234"),
+                ("", "test2"),
+                ("", "nokey"),
+                ("test4", "")
+            ],
+            cookies
+        );
+    }
+
+    #[test]
+    fn iter_yields_cookies_before_error() {
+        let mut cookies = Cookie::iter("test1=01; test2=0,2; test3=03");
+
+        let cookie = cookies.next().unwrap().unwrap();
+        assert_eq!("test1", cookie.name);
+        assert_eq!("01", cookie.value);
+
+        match cookies.next() {
+            Some(Err(Error::ParseError(err))) => assert_eq!(17, err.offset()),
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+
+        assert!(cookies.next().is_none());
+    }
+
+    #[test]
+    fn iter_empty() {
+        let mut cookies = Cookie::iter("");
+        assert!(matches!(cookies.next(), Some(Err(Error::ParseError(_)))));
+        assert!(cookies.next().is_none());
+
+        assert!(Cookie::iter(" ").next().unwrap().is_err());
+    }
+
+    #[test]
+    fn parse_many() {
+        let crumbs = ["a=1; b=\"2\"", " c=3 ", "=4"];
+        let cookies = Cookie::parse_many(crumbs.iter().copied()).unwrap();
+
+        let pairs = cookies
+            .iter()
+            .map(|cookie| (cookie.get_name(), cookie.get_value(), cookie.get_crumb()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![("a", "1", 0), ("b", "2", 0), ("c", "3", 1), ("", "4", 2)],
+            pairs
+        );
+
+        for cookie in &cookies {
+            let crumb = crumbs[cookie.get_crumb()];
+            assert_eq!(cookie.get_name(), &crumb[cookie.get_name_span()]);
+            assert_eq!(cookie.get_raw_value(), &crumb[cookie.get_value_span()]);
+        }
+    }
+
+    #[test]
+    fn parse_many_agrees_with_parse() {
+        let crumbs = ["a=1", "b=2; c=\"3\"", "d="];
+        assert_eq!(
+            Cookie::parse(&crumbs.join("; ")).unwrap(),
+            Cookie::parse_many(crumbs.iter().copied()).unwrap()
+        );
+
+        let cookies = Cookie::parse_many(vec!["a=1"]).unwrap();
+        assert_eq!(Cookie::parse("a=1").unwrap(), cookies);
+        assert_eq!(0, cookies[0].get_crumb());
+    }
+
+    #[test]
+    fn parse_many_errors() {
+        fn parse_many_error(crumbs: &[&str]) -> (ParseErrorKind, usize, usize) {
+            match Cookie::parse_many(crumbs.iter().copied()) {
+                Err(Error::ParseError(err)) => (err.kind(), err.crumb(), err.offset()),
+                result => panic!("expected a parse error, got {:?}", result),
+            }
+        }
+
+        assert_eq!(
+            (ParseErrorKind::UnexpectedCharacter, 1, 3),
+            parse_many_error(&["a=1", "b=x,y", "c=3"])
+        );
+        assert_eq!(
+            (ParseErrorKind::MissingSeparator, 0, 4),
+            parse_many_error(&["a=1;b=2", "c=3"])
+        );
+        assert_eq!(
+            (ParseErrorKind::UnexpectedEnd, 1, 0),
+            parse_many_error(&["a=1", ""])
+        );
+    }
+
+    #[test]
+    fn parse_many_without_crumbs() {
+        assert!(Cookie::parse_many(Vec::new()).unwrap().is_empty());
+    }
+
+    fn parse_and_filter<'a>(input: &'a str, name: &str) -> Option<Vec<(&'a str, &'a str)>> {
+        Cookie::parse(input).ok().map(|cookies| {
+            cookies
+                .into_iter()
+                .filter(|cookie| cookie.name == name)
+                .map(|cookie| (cookie.name, cookie.value))
+                .collect()
+        })
+    }
+
+    #[test]
+    fn find() {
+        const COOKIE_STR: &str = "test1=01; session=\"abc\"; session=def";
+        let cookie = Cookie::find(COOKIE_STR, "session").unwrap().unwrap();
+        assert_eq!("session", cookie.name);
+        assert_eq!("abc", cookie.value);
+
+        assert!(Cookie::find(COOKIE_STR, "Session").unwrap().is_none());
+        assert!(Cookie::find(COOKIE_STR, "").unwrap().is_none());
+    }
+
+    #[test]
+    fn find_empty_name() {
+        let cookie = Cookie::find("test1=01; nokey; =other", "")
+            .unwrap()
+            .unwrap();
+        assert_eq!("", cookie.name);
+        assert_eq!("nokey", cookie.value);
+    }
+
+    #[test]
+    fn find_validates_after_match() {
+        match Cookie::find("session=abc; test2=0,2", "session") {
+            Err(Error::ParseError(err)) => assert_eq!(20, err.offset()),
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn find_all() {
+        const COOKIE_STR: &str = "id=1; test2=02; id=\"2\"; ID=3";
+        let cookies = Cookie::find_all(COOKIE_STR, "id")
+            .unwrap()
+            .into_iter()
+            .map(|cookie| (cookie.name, cookie.value))
+            .collect::<Vec<(&str, &str)>>();
+
+        assert_eq!(vec![("id", "1"), ("id", "2")], cookies);
+        assert!(Cookie::find_all(COOKIE_STR, "missing").unwrap().is_empty());
+        assert!(Cookie::find_all("id=1; id=2;", "id").is_err());
+    }
+
+    #[test]
+    fn find_agrees_with_parse() {
+        const INPUTS: &[&str] = &[
+            "",
+            "a=1",
+            "a=1; a=2",
+            " a=\"1\"; b=2 ",
+            "a; =a; a=",
+            "a=1; b=,",
+            "a=1;b=2",
+            "a=\"1",
+        ];
+
+        for input in INPUTS {
+            for name in &["a", "b", "", "A"] {
+                let expected = parse_and_filter(input, name);
+
+                let found = Cookie::find(input, name)
+                    .ok()
+                    .map(|cookie| cookie.map(|c| (c.name, c.value)));
+                assert_eq!(
+                    expected.as_ref().map(|cookies| cookies.first().copied()),
+                    found,
+                    "{:?} {:?}",
+                    input,
+                    name
+                );
+
+                let found_all = Cookie::find_all(input, name).ok().map(|cookies| {
+                    cookies
+                        .into_iter()
+                        .map(|c| (c.name, c.value))
+                        .collect::<Vec<(&str, &str)>>()
+                });
+                assert_eq!(expected, found_all, "{:?} {:?}", input, name);
+            }
+        }
+    }
+
+    #[test]
+    fn parse_bytes() {
+        const COOKIE_STR: &str = " test1=0x1234; =\"test2\"; nokey; test4= ";
+        let parsed_cookies = Cookie::parse(COOKIE_STR).unwrap();
+        let parsed_bytes = Cookie::parse_bytes(COOKIE_STR.as_bytes()).unwrap();
+
+        assert_eq!(parsed_cookies.len(), parsed_bytes.len());
+        for (cookie, bytes) in parsed_cookies.iter().zip(parsed_bytes.iter()) {
+            assert_eq!(cookie.name.as_bytes(), bytes.get_name());
+            assert_eq!(cookie.value.as_bytes(), bytes.get_value());
+        }
+    }
+
+    #[test]
+    fn parse_bytes_rejects_non_ascii() {
+        match Cookie::parse_bytes(b"test1=01; test2=caf\xe9") {
+            Err(Error::ParseError(err)) => {
+                assert_eq!(ParseErrorKind::UnexpectedCharacter, err.kind());
+                assert_eq!(19, err.offset());
+                assert_eq!(Some(char::REPLACEMENT_CHARACTER), err.character());
+            }
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+
+        assert!(
+            Cookie::parse_bytes_with_policy("test=café".as_bytes(), NonAsciiPolicy::Reject)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parse_bytes_non_ascii_in_values() {
+        let parsed_cookies = Cookie::parse_bytes_with_policy(
+            b"test1=caf\xe9; test2=\"\x80\xff\"; \xe9t\xe9",
+            NonAsciiPolicy::AllowInValues,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                (&b"test1"[..], &b"caf\xe9"[..]),
+                (&b"test2"[..], &b"\x80\xff"[..]),
+                (&b""[..], &b"\xe9t\xe9"[..])
+            ],
+            parsed_cookies
+                .iter()
+                .map(|cookie| (cookie.get_name(), cookie.get_value()))
+                .collect::<Vec<(&[u8], &[u8])>>()
+        );
+    }
+
+    #[test]
+    fn parse_bytes_non_ascii_in_names() {
+        match Cookie::parse_bytes_with_policy(b"caf\xe9=1", NonAsciiPolicy::AllowInValues) {
+            Err(Error::ParseError(err)) => assert_eq!(4, err.offset()),
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn into_owned() {
+        let cookies = {
+            let input = String::from("test1=01; test2=\"02\"");
+            Cookie::parse(&input)
+                .unwrap()
+                .into_iter()
+                .map(Cookie::into_owned)
+                .collect::<Vec<CookieBuf>>()
+        };
+
+        assert_eq!("test1", cookies[0].get_name());
+        assert_eq!("01", cookies[0].get_value());
+        assert_eq!("test2", cookies[1].get_name());
+        assert_eq!("02", cookies[1].get_value());
+    }
+
+    #[test]
+    fn owned_and_borrowed_equality() {
+        let cookies = Cookie::parse("test1=01; test1=\"01\"; test1=02; test2=01").unwrap();
+        assert_eq!(cookies[0], cookies[1]);
+        assert_ne!(cookies[0], cookies[2]);
+        assert_ne!(cookies[0], cookies[3]);
+
+        let owned = cookies[0].clone().into_owned();
+        assert_eq!(cookies[1], owned);
+        assert_eq!(owned, cookies[1]);
+        assert_ne!(owned, cookies[2]);
+        assert_eq!(cookies[0], owned.as_cookie());
+        assert_eq!(owned, CookieBuf::from(cookies[1].clone()));
+    }
+
+    #[test]
+    fn hash() {
+        use std::collections::HashSet;
+
+        let cookies = Cookie::parse("test1=01; test1=\"01\"; test2=01").unwrap();
+        let borrowed = cookies.iter().cloned().collect::<HashSet<Cookie>>();
+        assert_eq!(2, borrowed.len());
+
+        let owned = cookies
+            .into_iter()
+            .map(Cookie::into_owned)
+            .collect::<HashSet<CookieBuf>>();
+        assert_eq!(2, owned.len());
+    }
+
+    #[test]
+    fn spans() {
+        const COOKIE_STR: &str = " test1=01; =\"test2\"; \"nokey\"; test4=";
+        let cookies = Cookie::parse(COOKIE_STR).unwrap();
+
+        let spans = cookies
+            .iter()
+            .map(|cookie| (cookie.get_name_span(), cookie.get_value_span()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (1..6, 7..9),
+                (11..11, 12..19),
+                (21..21, 21..28),
+                (30..35, 36..36)
+            ],
+            spans
+        );
+
+        for cookie in &cookies {
+            assert_eq!(cookie.name, &COOKIE_STR[cookie.get_name_span()]);
+            assert_eq!(cookie.get_raw_value(), &COOKIE_STR[cookie.get_value_span()]);
+        }
+    }
+
+    #[test]
+    fn raw_value() {
+        let cookies = Cookie::parse("a=\"x\"; a=x; b=\"=\"").unwrap();
+
+        assert_eq!("\"x\"", cookies[0].get_raw_value());
+        assert!(cookies[0].was_quoted());
+        assert_eq!("x", cookies[1].get_raw_value());
+        assert!(!cookies[1].was_quoted());
+        assert_eq!("=", cookies[2].get_value());
+        assert_eq!("\"=\"", cookies[2].get_raw_value());
+
+        assert_eq!(cookies[0], cookies[1]);
+    }
+
+    #[test]
+    fn spans_not_parsed() {
+        let cookie = Cookie::new("name", "value");
+        assert_eq!(0..4, cookie.get_name_span());
+        assert_eq!(5..10, cookie.get_value_span());
+        assert_eq!("value", cookie.get_raw_value());
+        assert!(!cookie.was_quoted());
+    }
+
+    #[test]
+    fn spans_recovering() {
+        const COOKIE_STR: &str = "a=1; b=,; c=\"3\"";
+        let recovered = Cookie::parse_recovering(COOKIE_STR);
+        let cookie = &recovered.get_cookies()[1];
+
+        assert_eq!(10..11, cookie.get_name_span());
+        assert_eq!(12..15, cookie.get_value_span());
+        assert!(cookie.was_quoted());
+    }
+
+    #[test]
+    fn spans_owned() {
+        let owned = Cookie::parse("a=1; b=\"2\"").unwrap()[1]
+            .clone()
+            .into_owned();
+
+        assert_eq!("2", owned.get_value());
+        let cookie = owned.as_cookie();
+        assert_eq!(5..6, cookie.get_name_span());
+        assert_eq!(7..10, cookie.get_value_span());
+        assert_eq!("\"2\"", cookie.get_raw_value());
+    }
+
+    #[test]
+    fn serialize() {
+        const COOKIE_STR: &str = " test1=0x1234; =\"test2\"; nokey; test4= ";
+        let cookies = Cookie::parse(COOKIE_STR).unwrap();
+        let header = Cookie::serialize(&cookies).unwrap();
+
+        assert_eq!("test1=0x1234; =test2; =nokey; test4=", header);
+        assert_eq!(cookies, Cookie::parse(&header).unwrap());
+
+        let no_cookies: Vec<Cookie> = Vec::new();
+        assert_eq!("", Cookie::serialize(&no_cookies).unwrap());
+    }
+
+    #[test]
+    fn serialize_invalid() {
+        let cookies = Cookie::parse_with_mode("a=1; b=two words", ParseMode::Lenient).unwrap();
+        assert!(matches!(
+            Cookie::serialize(&cookies),
+            Err(Error::BuildError(_))
+        ));
+    }
 }
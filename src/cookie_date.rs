@@ -0,0 +1,513 @@
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+const WEEKDAY_NAMES: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Parses a cookie date, such as the value of the `Expires` attribute, using the forgiving
+/// [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-5.1.1) algorithm.
+///
+/// Returns `None` if the date cannot be parsed or is before the year 1601.
+///
+/// # Examples
+///
+/// ```
+/// use basic_cookies::parse_cookie_date;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let expected = Some(UNIX_EPOCH + Duration::from_secs(784_111_777));
+///
+/// assert_eq!(expected, parse_cookie_date("Sun, 06 Nov 1994 08:49:37 GMT"));
+/// assert_eq!(expected, parse_cookie_date("Sunday, 06-Nov-94 08:49:37 GMT"));
+/// assert_eq!(expected, parse_cookie_date("Sun Nov  6 08:49:37 1994"));
+/// assert_eq!(None, parse_cookie_date("Sun, 06 Nov 1994"));
+/// ```
+pub fn parse_cookie_date(input: &str) -> Option<SystemTime> {
+    let mut time = None;
+    let mut day_of_month = None;
+    let mut month = None;
+    let mut year = None;
+
+    for token in input
+        .as_bytes()
+        .split(|b| is_delimiter(*b))
+        .filter(|token| !token.is_empty())
+    {
+        if time.is_none() {
+            if let Some(val) = parse_time(token) {
+                time = Some(val);
+                continue;
+            }
+        }
+
+        if day_of_month.is_none() {
+            if let Some(val) = parse_digits(token, 1, 2) {
+                day_of_month = Some(val);
+                continue;
+            }
+        }
+
+        if month.is_none() {
+            if let Some(val) = parse_month(token) {
+                month = Some(val);
+                continue;
+            }
+        }
+
+        if year.is_none() {
+            if let Some(val) = parse_digits(token, 2, 4) {
+                year = Some(val);
+                continue;
+            }
+        }
+    }
+
+    let (hour, minute, second) = time?;
+    let day_of_month = day_of_month?;
+    let month = month?;
+    let year = match year? {
+        year @ 70..=99 => year + 1900,
+        year @ 0..=69 => year + 2000,
+        year => year,
+    };
+
+    if year < 1601
+        || day_of_month < 1
+        || day_of_month > days_in_month(year, month)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    let seconds = days_from_civil(year, month, day_of_month) * SECONDS_PER_DAY
+        + hour * 3600
+        + minute * 60
+        + second;
+
+    if seconds >= 0 {
+        UNIX_EPOCH.checked_add(Duration::from_secs(seconds as u64))
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.unsigned_abs()))
+    }
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(b, 0x09 | 0x20..=0x2f | 0x3b..=0x40 | 0x5b..=0x60 | 0x7b..=0x7e)
+}
+
+/// Matches `hms-time [ non-digit *OCTET ]`.
+fn parse_time(token: &[u8]) -> Option<(i64, i64, i64)> {
+    let (hour, rest) = parse_digits_prefix(token, 1, 2)?;
+    let (minute, rest) = parse_digits_prefix(rest.strip_prefix(b":")?, 1, 2)?;
+    let (second, rest) = parse_digits_prefix(rest.strip_prefix(b":")?, 1, 2)?;
+    if starts_with_digit(rest) {
+        return None;
+    }
+
+    Some((hour, minute, second))
+}
+
+/// Matches `min*maxDIGIT [ non-digit *OCTET ]`.
+fn parse_digits(token: &[u8], min: usize, max: usize) -> Option<i64> {
+    let (val, rest) = parse_digits_prefix(token, min, max)?;
+    if starts_with_digit(rest) {
+        return None;
+    }
+
+    Some(val)
+}
+
+fn parse_digits_prefix(token: &[u8], min: usize, max: usize) -> Option<(i64, &[u8])> {
+    let len = token
+        .iter()
+        .take(max)
+        .take_while(|b| b.is_ascii_digit())
+        .count();
+    if len < min {
+        return None;
+    }
+
+    let val = token[..len]
+        .iter()
+        .fold(0_i64, |acc, b| acc * 10 + i64::from(b - b'0'));
+    Some((val, &token[len..]))
+}
+
+fn starts_with_digit(token: &[u8]) -> bool {
+    matches!(token.first(), Some(b) if b.is_ascii_digit())
+}
+
+/// Matches `month *OCTET`, case-insensitively.
+fn parse_month(token: &[u8]) -> Option<i64> {
+    let prefix = token.get(..3)?;
+    MONTH_NAMES
+        .iter()
+        .position(|name| prefix.eq_ignore_ascii_case(name.as_bytes()))
+        .map(|idx| idx as i64 + 1)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Formats a time as an [IMF-fixdate](https://tools.ietf.org/html/rfc7231#section-7.1.1.1),
+/// such as `Sun, 06 Nov 1994 08:49:37 GMT`, for use as the value of the `Expires` attribute.
+///
+/// Sub-second precision is discarded, so formatting and then parsing with `parse_cookie_date`
+/// yields the time truncated to the whole second.
+/// Returns `None` for times outside of the years 1601 to 9999, which cannot be parsed back.
+///
+/// # Examples
+///
+/// ```
+/// use basic_cookies::{format_cookie_date, parse_cookie_date};
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let time = UNIX_EPOCH + Duration::from_secs(784_111_777);
+/// let formatted = format_cookie_date(time).unwrap();
+///
+/// assert_eq!("Sun, 06 Nov 1994 08:49:37 GMT", formatted);
+/// assert_eq!(Some(time), parse_cookie_date(&formatted));
+/// ```
+pub fn format_cookie_date(time: SystemTime) -> Option<String> {
+    let seconds = seconds_since_epoch(time)?;
+    let days = seconds.div_euclid(SECONDS_PER_DAY);
+    let seconds_of_day = seconds.rem_euclid(SECONDS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+
+    if !(1601..=9999).contains(&year) {
+        return None;
+    }
+
+    Some(format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+        WEEKDAY_NAMES[(days + 4).rem_euclid(7) as usize],
+        day,
+        MONTH_NAMES[(month - 1) as usize],
+        year,
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60
+    ))
+}
+
+/// Whole seconds relative to the Unix epoch, rounded towards negative infinity.
+fn seconds_since_epoch(time: SystemTime) -> Option<i64> {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => i64::try_from(duration.as_secs()).ok(),
+        Err(err) => {
+            let duration = err.duration();
+            let seconds = i64::try_from(duration.as_secs()).ok()?;
+            Some(if duration.subsec_nanos() > 0 {
+                -seconds - 1
+            } else {
+                -seconds
+            })
+        }
+    }
+}
+
+/// Converts a proleptic Gregorian date to days since the Unix epoch.
+///
+/// See Howard Hinnant's [`chrono`-Compatible Low-Level Date Algorithms](https://howardhinnant.github.io/date_algorithms.html).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Converts days since the Unix epoch to a proleptic Gregorian `(year, month, day)`.
+///
+/// See Howard Hinnant's [`chrono`-Compatible Low-Level Date Algorithms](https://howardhinnant.github.io/date_algorithms.html).
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_cookie_date, parse_cookie_date};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    fn ymd_hms(year: i64, month: i64, day: i64, hour: u64, minute: u64, second: u64) -> SystemTime {
+        let days = super::days_from_civil(year, month, day);
+        let seconds = hour * 3600 + minute * 60 + second;
+        if days >= 0 {
+            UNIX_EPOCH + Duration::from_secs(days as u64 * 86_400 + seconds)
+        } else {
+            UNIX_EPOCH - Duration::from_secs(days.unsigned_abs() * 86_400)
+                + Duration::from_secs(seconds)
+        }
+    }
+
+    #[test]
+    fn parse_valid_dates() {
+        let vectors = [
+            (
+                "Mon, 10-Dec-2007 17:02:24 GMT",
+                ymd_hms(2007, 12, 10, 17, 2, 24),
+            ),
+            (
+                "Wed, 09 Dec 2009 16:27:23 GMT",
+                ymd_hms(2009, 12, 9, 16, 27, 23),
+            ),
+            (
+                "Thursday, 01-Jan-1970 00:00:00 GMT",
+                ymd_hms(1970, 1, 1, 0, 0, 0),
+            ),
+            (
+                "Mon Dec 10 16:32:30 2007 GMT",
+                ymd_hms(2007, 12, 10, 16, 32, 30),
+            ),
+            (
+                "Wednesday, 01-Jan-10 00:00:00 GMT",
+                ymd_hms(2010, 1, 1, 0, 0, 0),
+            ),
+            (
+                "Mon, 10-Dec-07 20:35:03 GMT",
+                ymd_hms(2007, 12, 10, 20, 35, 3),
+            ),
+            ("Wed, 1 Jan 2020 00:00:00 GMT", ymd_hms(2020, 1, 1, 0, 0, 0)),
+            (
+                "Saturday, 8-Dec-2012 21:24:09 GMT",
+                ymd_hms(2012, 12, 8, 21, 24, 9),
+            ),
+            (
+                "Thursday, 01-Jan-1970 00:00:00 GMT",
+                ymd_hms(1970, 1, 1, 0, 0, 0),
+            ),
+            (
+                "Tue, 18-Oct-2011 07:42:42.123 GMT",
+                ymd_hms(2011, 10, 18, 7, 42, 42),
+            ),
+            (
+                "Thu, 19/Apr\\2007 16:00:00 GMT",
+                ymd_hms(2007, 4, 19, 16, 0, 0),
+            ),
+            (
+                "Fri, 1 Jan 2010 01:01:50 GMT",
+                ymd_hms(2010, 1, 1, 1, 1, 50),
+            ),
+            (
+                "Wednesday, 1-Jan-2003 00:00:00 GMT",
+                ymd_hms(2003, 1, 1, 0, 0, 0),
+            ),
+            (", 1-Jan-2003 00:00:00 GMT", ymd_hms(2003, 1, 1, 0, 0, 0)),
+            (" 1-Jan-2003 00:00:00 GMT", ymd_hms(2003, 1, 1, 0, 0, 0)),
+            ("1-Jan-2003 00:00:00 GMT", ymd_hms(2003, 1, 1, 0, 0, 0)),
+            (
+                "Wed,18-Apr-07 22:50:12 GMT",
+                ymd_hms(2007, 4, 18, 22, 50, 12),
+            ),
+            (
+                "WillyWonka  , 18-Apr-07 22:50:12 GMT",
+                ymd_hms(2007, 4, 18, 22, 50, 12),
+            ),
+            (
+                "WillyWonka  , 18-Apr-07 22:50:12",
+                ymd_hms(2007, 4, 18, 22, 50, 12),
+            ),
+            (
+                "WillyWonka  ,  18-apr-07   22:50:12",
+                ymd_hms(2007, 4, 18, 22, 50, 12),
+            ),
+            (
+                "Mon, 18-Apr-1977 22:50:13 GMT",
+                ymd_hms(1977, 4, 18, 22, 50, 13),
+            ),
+            (
+                "Mon, 18-Apr-77 22:50:13 GMT",
+                ymd_hms(1977, 4, 18, 22, 50, 13),
+            ),
+            (
+                "\"Sat, 15-Apr-17\\\"21:01:22\\\"GMT\"",
+                ymd_hms(2017, 4, 15, 21, 1, 22),
+            ),
+            (
+                "Partyday, 18- April-07 22:50:12",
+                ymd_hms(2007, 4, 18, 22, 50, 12),
+            ),
+            (
+                "Partyday, 18 - Apri-07 22:50:12",
+                ymd_hms(2007, 4, 18, 22, 50, 12),
+            ),
+            (
+                "Wednes, 1-Januar-2003 00:00:00 GMT",
+                ymd_hms(2003, 1, 1, 0, 0, 0),
+            ),
+            ("Sat, 15-Apr-17 21:01:22", ymd_hms(2017, 4, 15, 21, 1, 22)),
+            (
+                "Sat, 15-Apr-17 21:01:22 GMT-2",
+                ymd_hms(2017, 4, 15, 21, 1, 22),
+            ),
+            (
+                "Sat, 15-Apr-17 21:01:22 GMT BLAH",
+                ymd_hms(2017, 4, 15, 21, 1, 22),
+            ),
+            (
+                "Sat, 15-Apr-17 21:01:22 GMT 2017",
+                ymd_hms(2017, 4, 15, 21, 1, 22),
+            ),
+            (
+                "Sat, 15-Apr-17 21:01:22 GMT 2017 XYZ",
+                ymd_hms(2017, 4, 15, 21, 1, 22),
+            ),
+            ("15-Apr-17 21:01:22", ymd_hms(2017, 4, 15, 21, 1, 22)),
+            ("Apr-15-17 21:01:22", ymd_hms(2017, 4, 15, 21, 1, 22)),
+            ("17-Apr-15 21:01:22", ymd_hms(2015, 4, 17, 21, 1, 22)),
+            ("21:01:22 Sat Apr 15 2017", ymd_hms(2017, 4, 15, 21, 1, 22)),
+            ("1:2:3 1st Jan 2017", ymd_hms(2017, 1, 1, 1, 2, 3)),
+            (
+                "Sat, 29 Feb 2020 00:00:00 GMT",
+                ymd_hms(2020, 2, 29, 0, 0, 0),
+            ),
+            (
+                "Mon, 01 Jan 1601 00:00:00 GMT",
+                ymd_hms(1601, 1, 1, 0, 0, 0),
+            ),
+            (
+                "Fri, 31 Dec 9999 23:59:59 GMT",
+                ymd_hms(9999, 12, 31, 23, 59, 59),
+            ),
+        ];
+
+        for (input, expected) in vectors.iter() {
+            assert_eq!(Some(*expected), parse_cookie_date(input), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn parse_invalid_dates() {
+        let vectors = [
+            "",
+            "(invalid)",
+            "Thu, 10 Dec 2007",
+            "16:32:30",
+            "Thu, 10 Dec 16:32:30 GMT",
+            "Thu, 10 2007 16:32:30 GMT",
+            "Thu, Dec 2007 16:32:30 GMT",
+            "Wed, 09 Dec 1600 16:27:23 GMT",
+            "Sun, 31 Dec 1600 23:59:59 GMT",
+            "Sat, 30 Feb 2008 00:00:00 GMT",
+            "Mon, 29 Feb 2021 00:00:00 GMT",
+            "Thu, 10 Dec 2007 25:00:00 GMT",
+            "Sun, 16-Nov-2008 24:00:00 GMT",
+            "Sun, 16-Nov-2008 23:60:00 GMT",
+            "Sun, 16-Nov-2008 23:59:60 GMT",
+            "Mon, 00-Dec-2007 16:32:30 GMT",
+            "Mon, 32-Dec-2007 16:32:30 GMT",
+            "Sat, 15 Apr 99999 21:01:22 GMT",
+            "Sat, 15 Apr 7 21:01:22 GMT",
+            "Sat, 15 Apr 2017 21:01:222 GMT",
+            "Sat, 15 Apr 2017 21::22 GMT",
+            "Sat, 150 Apr 2017 21:01:22 GMT",
+        ];
+
+        for input in vectors.iter() {
+            assert_eq!(None, parse_cookie_date(input), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn format_epoch() {
+        assert_eq!(
+            Some("Thu, 01 Jan 1970 00:00:00 GMT"),
+            format_cookie_date(UNIX_EPOCH).as_deref()
+        );
+    }
+
+    #[test]
+    fn format_rfc_example() {
+        assert_eq!(
+            Some("Sun, 06 Nov 1994 08:49:37 GMT"),
+            format_cookie_date(UNIX_EPOCH + Duration::from_secs(784_111_777)).as_deref()
+        );
+    }
+
+    #[test]
+    fn format_before_epoch() {
+        assert_eq!(
+            Some("Wed, 31 Dec 1969 23:59:59 GMT"),
+            format_cookie_date(UNIX_EPOCH - Duration::from_millis(500)).as_deref()
+        );
+    }
+
+    #[test]
+    fn format_leap_day() {
+        assert_eq!(
+            Some("Tue, 29 Feb 2000 12:00:00 GMT"),
+            format_cookie_date(ymd_hms(2000, 2, 29, 12, 0, 0)).as_deref()
+        );
+    }
+
+    #[test]
+    fn format_bounds() {
+        assert_eq!(
+            Some("Mon, 01 Jan 1601 00:00:00 GMT"),
+            format_cookie_date(ymd_hms(1601, 1, 1, 0, 0, 0)).as_deref()
+        );
+        assert_eq!(
+            Some("Fri, 31 Dec 9999 23:59:59 GMT"),
+            format_cookie_date(ymd_hms(9999, 12, 31, 23, 59, 59)).as_deref()
+        );
+        assert_eq!(None, format_cookie_date(ymd_hms(1600, 12, 31, 23, 59, 59)));
+        assert_eq!(None, format_cookie_date(ymd_hms(10000, 1, 1, 0, 0, 0)));
+    }
+
+    #[test]
+    fn round_trip() {
+        let first = ymd_hms(1601, 1, 1, 0, 0, 0);
+        let last = ymd_hms(9999, 12, 31, 23, 59, 59);
+
+        // Steps through the whole range with a stride that is coprime to the length of a day,
+        // so that every time of day and every day of the 400-year cycle is visited eventually.
+        let mut time = first;
+        while time <= last {
+            let formatted = format_cookie_date(time).unwrap();
+            assert_eq!(Some(time), parse_cookie_date(&formatted), "{}", formatted);
+            time += Duration::from_secs(1_234_567);
+        }
+    }
+
+    #[test]
+    fn round_trip_truncates_subseconds() {
+        let time = ymd_hms(2021, 3, 14, 1, 59, 26);
+        let formatted = format_cookie_date(time + Duration::from_millis(535)).unwrap();
+        assert_eq!(Some(time), parse_cookie_date(&formatted));
+
+        let time = ymd_hms(1969, 7, 20, 20, 17, 40);
+        let formatted = format_cookie_date(time + Duration::from_millis(999)).unwrap();
+        assert_eq!(Some(time), parse_cookie_date(&formatted));
+    }
+}
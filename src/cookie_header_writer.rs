@@ -0,0 +1,264 @@
+use super::token_class::{is_cookie_name, is_cookie_octets};
+use super::{BuildError, BuildErrorKind, Cookie, Error};
+use std::fmt::Write;
+
+/// Writes a `Cookie` header value into a `fmt::Write`, one cookie at a time.
+///
+/// Only cookies that `Cookie::parse` reads back with the same name and value are written: the
+/// name has to be empty or a token, and the value has to be made of cookie-octets and `=`.
+/// Values are never quoted, since the strict grammar accepts the same characters in quoted
+/// and unquoted values.
+///
+/// # Examples
+///
+/// ```
+/// use basic_cookies::{Cookie, CookieHeaderWriter};
+///
+/// let mut writer = CookieHeaderWriter::new(String::new());
+/// writer.write("SID", "31d4d96e407aad42").unwrap();
+/// writer.write("lang", "en-US").unwrap();
+///
+/// assert!(writer.write("theme", "dark mode").is_err());
+///
+/// let header = writer.into_inner();
+/// assert_eq!("SID=31d4d96e407aad42; lang=en-US", header);
+/// assert_eq!(2, Cookie::parse(&header).unwrap().len());
+/// ```
+#[derive(Debug)]
+pub struct CookieHeaderWriter<W> {
+    writer: W,
+    is_empty: bool,
+}
+
+impl<W: Write> CookieHeaderWriter<W> {
+    /// Creates a writer that appends a `Cookie` header value to the given `fmt::Write`.
+    pub fn new(writer: W) -> CookieHeaderWriter<W> {
+        CookieHeaderWriter {
+            writer,
+            is_empty: true,
+        }
+    }
+
+    /// Writes a cookie with the given name and value, preceded by a `"; "` separator unless it is
+    /// the first one.
+    ///
+    /// Nothing is written if the name or the value would not be read back identically, in which
+    /// case the error is a `BuildErrorKind::InvalidName` or `BuildErrorKind::InvalidValue`.
+    /// A failure of the underlying writer is a `BuildErrorKind::WriteFailed`.
+    pub fn write(&mut self, name: &str, value: &str) -> Result<(), Error> {
+        if !is_cookie_name(name) {
+            return Err(BuildError::new(BuildErrorKind::InvalidName).into_error());
+        }
+
+        if !is_cookie_octets(value) {
+            return Err(BuildError::new(BuildErrorKind::InvalidValue).into_error());
+        }
+
+        self.write_pair(name, value)
+            .map_err(|_| BuildError::new(BuildErrorKind::WriteFailed).into_error())
+    }
+
+    /// Writes the name and the value of a cookie, as `write` does. Quoted values are written
+    /// unquoted.
+    pub fn write_cookie(&mut self, cookie: &Cookie) -> Result<(), Error> {
+        self.write(cookie.get_name(), cookie.get_value())
+    }
+
+    /// Determines whether no cookies have been written yet.
+    pub fn is_empty(&self) -> bool {
+        self.is_empty
+    }
+
+    /// Takes the underlying `fmt::Write`.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_pair(&mut self, name: &str, value: &str) -> std::fmt::Result {
+        if !self.is_empty {
+            self.writer.write_str("; ")?;
+        }
+
+        self.writer.write_str(name)?;
+        self.writer.write_char('=')?;
+        self.writer.write_str(value)?;
+        self.is_empty = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CookieHeaderWriter;
+    use crate::{BuildErrorKind, Cookie, Error};
+
+    const ALPHABET: &[char] = &['a', '(', '=', '"', ';', ' ', ',', 'é'];
+
+    /// Every string of up to `max_len` characters of the alphabet.
+    fn strings(max_len: u32) -> Vec<String> {
+        let mut strings = Vec::new();
+        for len in 0..=max_len {
+            for mut idx in 0..ALPHABET.len().pow(len) {
+                let mut s = String::new();
+                for _ in 0..len {
+                    s.push(ALPHABET[idx % ALPHABET.len()]);
+                    idx /= ALPHABET.len();
+                }
+                strings.push(s);
+            }
+        }
+        strings
+    }
+
+    fn parses_back(header: &str, name: &str, value: &str) -> bool {
+        match Cookie::parse(header) {
+            Ok(cookies) => {
+                cookies.len() == 1
+                    && cookies[0].get_name() == name
+                    && cookies[0].get_value() == value
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn build_error_kind(result: Result<(), Error>) -> BuildErrorKind {
+        match result {
+            Err(Error::BuildError(err)) => err.kind(),
+            res => panic!("expected build error, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn write() {
+        let mut writer = CookieHeaderWriter::new(String::new());
+        assert!(writer.is_empty());
+
+        writer.write("a", "1").unwrap();
+        writer.write("", "nokey").unwrap();
+        writer.write("b", "x=(y)").unwrap();
+        writer.write("c", "").unwrap();
+        assert!(!writer.is_empty());
+
+        assert_eq!("a=1; =nokey; b=x=(y); c=", writer.into_inner());
+    }
+
+    #[test]
+    fn write_cookie_unquotes() {
+        let cookies = Cookie::parse("a=\"1\"; b=2").unwrap();
+        let mut writer = CookieHeaderWriter::new(String::new());
+        for cookie in &cookies {
+            writer.write_cookie(cookie).unwrap();
+        }
+
+        assert_eq!("a=1; b=2", writer.into_inner());
+    }
+
+    #[test]
+    fn invalid() {
+        let mut writer = CookieHeaderWriter::new(String::new());
+        assert_eq!(
+            BuildErrorKind::InvalidName,
+            build_error_kind(writer.write("a(", "1"))
+        );
+        assert_eq!(
+            BuildErrorKind::InvalidName,
+            build_error_kind(writer.write("a=b", "1"))
+        );
+        assert_eq!(
+            BuildErrorKind::InvalidValue,
+            build_error_kind(writer.write("a", "\"1\""))
+        );
+        assert_eq!(
+            BuildErrorKind::InvalidValue,
+            build_error_kind(writer.write("a", "1; b=2"))
+        );
+        assert_eq!(
+            BuildErrorKind::InvalidValue,
+            build_error_kind(writer.write("a", "é"))
+        );
+
+        assert!(writer.is_empty());
+        assert_eq!("", writer.into_inner());
+    }
+
+    #[test]
+    fn write_failed() {
+        struct FailingWriter;
+
+        impl std::fmt::Write for FailingWriter {
+            fn write_str(&mut self, _: &str) -> std::fmt::Result {
+                Err(std::fmt::Error)
+            }
+        }
+
+        let mut writer = CookieHeaderWriter::new(FailingWriter);
+        assert_eq!(
+            BuildErrorKind::WriteFailed,
+            build_error_kind(writer.write("a", "1"))
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let names = strings(2);
+        let values = strings(3);
+
+        for name in &names {
+            for value in &values {
+                let mut writer = CookieHeaderWriter::new(String::new());
+                match writer.write(name, value) {
+                    Ok(()) => assert!(
+                        parses_back(&writer.into_inner(), name, value),
+                        "{:?} {:?}",
+                        name,
+                        value
+                    ),
+                    // Only pairs that no header reads back identically may be rejected.
+                    Err(_) => {
+                        assert!(
+                            !parses_back(&format!("{}={}", name, value), name, value),
+                            "{:?} {:?}",
+                            name,
+                            value
+                        );
+                        assert!(
+                            !parses_back(&format!("{}=\"{}\"", name, value), name, value),
+                            "{:?} {:?}",
+                            name,
+                            value
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_many() {
+        let values = strings(2);
+
+        for (idx, value) in values.iter().enumerate() {
+            let pairs = [
+                ("a", value.as_str()),
+                ("", values[(idx * 7) % values.len()].as_str()),
+                ("b", values[(idx * 13) % values.len()].as_str()),
+            ];
+
+            let mut writer = CookieHeaderWriter::new(String::new());
+            let result = pairs
+                .iter()
+                .try_for_each(|(name, value)| writer.write(name, value));
+            if result.is_err() {
+                continue;
+            }
+
+            let header = writer.into_inner();
+            let parsed_pairs = Cookie::parse(&header)
+                .unwrap()
+                .iter()
+                .map(|cookie| (cookie.get_name(), cookie.get_value()))
+                .collect::<Vec<(&str, &str)>>();
+            assert_eq!(pairs.to_vec(), parsed_pairs, "{:?}", header);
+        }
+    }
+}
@@ -0,0 +1,1327 @@
+use super::domain::{canonicalize_host, domain_match};
+use super::path::{default_path, path_match};
+use super::token_class::{is_cookie_name, is_cookie_value};
+use super::{Cookie, RequestUrl, SetCookie, SetCookieAttribute};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "psl")]
+use super::is_public_suffix;
+
+#[cfg(not(feature = "psl"))]
+fn is_public_suffix(_domain: &str) -> bool {
+    false
+}
+
+/// A user agent cookie store implementing the
+/// [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-5.3) storage model.
+///
+/// # Examples
+///
+/// ```
+/// use basic_cookies::{CookieJar, RequestContext, RequestUrl, SetCookie};
+///
+/// let mut jar = CookieJar::new();
+/// let url = RequestUrl::parse("https://www.example.com/docs/index.html").unwrap();
+/// let set_cookie = SetCookie::parse("SID=31d4d96e407aad42; Domain=example.com").unwrap();
+///
+/// assert!(jar.store(&set_cookie, &url, &RequestContext::http()));
+///
+/// let stored = jar.iter().next().unwrap();
+/// assert_eq!("SID", stored.get_name());
+/// assert_eq!("example.com", stored.get_domain());
+/// assert_eq!("/docs", stored.get_path());
+/// assert!(!stored.is_host_only());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CookieJar {
+    cookies: Vec<StoredCookie>,
+    limits: CookieJarLimits,
+}
+
+/// The maximum number of cookies a `CookieJar` holds, in total and for each cookie domain.
+///
+/// The default limits are the minimum capacities recommended by
+/// [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-6.1): 50 cookies per domain and
+/// 3000 cookies in total.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CookieJarLimits {
+    per_domain: usize,
+    total: usize,
+}
+
+/// The outcome of storing a cookie with `CookieJar::store_with_report`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoreReport {
+    stored: bool,
+    evicted: Vec<EvictedCookie>,
+}
+
+/// A cookie that a `CookieJar` evicted to stay within its limits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvictedCookie {
+    cookie: StoredCookie,
+    reason: EvictionReason,
+}
+
+/// The reason a cookie was evicted from a `CookieJar`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EvictionReason {
+    /// The cookie had expired.
+    Expired,
+    /// The cookie was the least recently accessed one of a domain with too many cookies.
+    DomainLimit,
+    /// The cookie was the least recently accessed one of a jar with too many cookies.
+    TotalLimit,
+}
+
+/// A cookie held by a `CookieJar`, together with the metadata the storage model requires.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredCookie {
+    name: String,
+    value: String,
+    expiry_time: Option<SystemTime>,
+    domain: String,
+    path: String,
+    creation_time: SystemTime,
+    last_access_time: SystemTime,
+    persistent: bool,
+    host_only: bool,
+    secure_only: bool,
+    http_only: bool,
+}
+
+/// Describes the circumstances under which cookies are stored or retrieved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequestContext {
+    now: SystemTime,
+    http: bool,
+}
+
+impl CookieJar {
+    /// Creates an empty cookie jar with the default limits.
+    pub fn new() -> CookieJar {
+        CookieJar::with_limits(CookieJarLimits::default())
+    }
+
+    /// Creates an empty cookie jar with the given limits.
+    pub fn with_limits(limits: CookieJarLimits) -> CookieJar {
+        CookieJar {
+            cookies: Vec::new(),
+            limits,
+        }
+    }
+
+    /// Gets the limits of the jar.
+    pub fn get_limits(&self) -> CookieJarLimits {
+        self.limits
+    }
+
+    /// Stores a cookie received in response to a request for `url`.
+    ///
+    /// Returns `false` if the storage model requires the cookie to be ignored, for example
+    /// because its `Domain` attribute does not domain-match the request host. A cookie that has
+    /// already expired is accepted, and removes any cookie it would have replaced.
+    ///
+    /// Cookies are evicted as described in `store_with_report` if the jar exceeds its limits.
+    pub fn store(
+        &mut self,
+        set_cookie: &SetCookie,
+        url: &RequestUrl,
+        context: &RequestContext,
+    ) -> bool {
+        self.store_with_report(set_cookie, url, context).is_stored()
+    }
+
+    /// Stores a cookie like `store`, and reports the cookies evicted to make room for it.
+    ///
+    /// If storing the cookie exceeds the limits of the jar, every expired cookie is evicted
+    /// first. Then, if a cookie domain still holds too many cookies, its least recently accessed
+    /// cookies are evicted, and if the jar still holds too many cookies in total, the least
+    /// recently accessed cookies of the whole jar are evicted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::{
+    ///     CookieJar, CookieJarLimits, EvictionReason, RequestContext, RequestUrl, SetCookie,
+    /// };
+    ///
+    /// let mut jar = CookieJar::with_limits(CookieJarLimits::new(1, 10));
+    /// let url = RequestUrl::parse("https://example.com/").unwrap();
+    /// let context = RequestContext::http();
+    ///
+    /// jar.store(&SetCookie::parse("a=1").unwrap(), &url, &context);
+    /// let report = jar.store_with_report(&SetCookie::parse("b=2").unwrap(), &url, &context);
+    ///
+    /// assert!(report.is_stored());
+    /// assert_eq!("a", report.get_evicted()[0].get_cookie().get_name());
+    /// assert_eq!(EvictionReason::DomainLimit, report.get_evicted()[0].get_reason());
+    /// ```
+    pub fn store_with_report(
+        &mut self,
+        set_cookie: &SetCookie,
+        url: &RequestUrl,
+        context: &RequestContext,
+    ) -> StoreReport {
+        let mut report = StoreReport {
+            stored: false,
+            evicted: Vec::new(),
+        };
+
+        let mut cookie = match StoredCookie::from_set_cookie(set_cookie, url, context) {
+            Some(cookie) => cookie,
+            None => return report,
+        };
+
+        let old_idx = self
+            .cookies
+            .iter()
+            .position(|old| old.same_identity(&cookie));
+
+        if let Some(idx) = old_idx {
+            if self.cookies[idx].http_only && !context.http {
+                return report;
+            }
+
+            cookie.creation_time = self.cookies[idx].creation_time;
+        }
+
+        report.stored = true;
+        if cookie.is_expired(context.now) {
+            if let Some(idx) = old_idx {
+                self.cookies.remove(idx);
+            }
+            return report;
+        }
+
+        // A replaced cookie keeps its place, so that the jar stays in creation order. The number
+        // of cookies does not change, so there is nothing to evict.
+        if let Some(idx) = old_idx {
+            self.cookies[idx] = cookie;
+            return report;
+        }
+
+        let domain = cookie.domain.clone();
+        self.cookies.push(cookie);
+
+        let over_domain_limit = self.count_domain(&domain) > self.limits.per_domain;
+        if over_domain_limit || self.cookies.len() > self.limits.total {
+            report.evicted = self.evict_expired(context.now);
+        }
+
+        while self.count_domain(&domain) > self.limits.per_domain {
+            let idx = self
+                .least_recently_accessed(|cookie| cookie.domain == domain)
+                .unwrap();
+            report
+                .evicted
+                .push(self.evict(idx, EvictionReason::DomainLimit));
+        }
+
+        while self.cookies.len() > self.limits.total {
+            let idx = self.least_recently_accessed(|_| true).unwrap();
+            report
+                .evicted
+                .push(self.evict(idx, EvictionReason::TotalLimit));
+        }
+
+        report
+    }
+
+    /// Evicts every cookie that has expired at the given time, and returns them.
+    ///
+    /// Long-lived jars can call this periodically, since expired cookies are otherwise only
+    /// evicted when generating a `Cookie` header or when the jar exceeds its limits.
+    pub fn evict_expired(&mut self, now: SystemTime) -> Vec<EvictedCookie> {
+        let mut evicted = Vec::new();
+        let mut idx = 0;
+        while idx < self.cookies.len() {
+            if self.cookies[idx].is_expired(now) {
+                evicted.push(self.evict(idx, EvictionReason::Expired));
+            } else {
+                idx += 1;
+            }
+        }
+
+        evicted
+    }
+
+    /// Generates the value of the `Cookie` header for a request to `url`, following
+    /// [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-5.4).
+    ///
+    /// Cookies are selected by domain, path and the `Secure` and `HttpOnly` flags, and ordered
+    /// by longest path first, then earliest creation time. The last access time of every selected
+    /// cookie is updated and expired cookies are evicted. Returns `None` if no cookies apply.
+    ///
+    /// Cookies that `Cookie::parse` could not read back are skipped: those whose name is not a
+    /// token, and those whose value is not made of cookie-octets, such as values with spaces or
+    /// commas. Their last access time is not updated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::{Cookie, CookieJar, RequestContext, RequestUrl, SetCookie};
+    ///
+    /// let mut jar = CookieJar::new();
+    /// let url = RequestUrl::parse("https://www.example.com/docs/index.html").unwrap();
+    /// let context = RequestContext::http();
+    ///
+    /// jar.store(&SetCookie::parse("lang=en-US; Path=/").unwrap(), &url, &context);
+    /// jar.store(&SetCookie::parse("SID=31d4d96e407aad42").unwrap(), &url, &context);
+    ///
+    /// let header = jar.cookie_header_for(&url, &context).unwrap();
+    /// assert_eq!("SID=31d4d96e407aad42; lang=en-US", header);
+    ///
+    /// let parsed_cookies = Cookie::parse(&header).unwrap();
+    /// assert_eq!("SID", parsed_cookies[0].get_name());
+    /// assert_eq!("lang", parsed_cookies[1].get_name());
+    /// ```
+    pub fn cookie_header_for(
+        &mut self,
+        url: &RequestUrl,
+        context: &RequestContext,
+    ) -> Option<String> {
+        self.cookies
+            .retain(|cookie| !cookie.is_expired(context.now));
+
+        let request_host = canonicalize_host(url.get_host())?;
+
+        let mut selected = self
+            .cookies
+            .iter_mut()
+            .filter(|cookie| {
+                cookie.matches_host(&request_host)
+                    && path_match(url.get_path(), &cookie.path)
+                    && (!cookie.secure_only || url.is_secure())
+                    && (!cookie.http_only || context.http)
+                    && cookie.is_writable()
+            })
+            .collect::<Vec<&mut StoredCookie>>();
+
+        if selected.is_empty() {
+            return None;
+        }
+
+        selected.sort_by(|a, b| {
+            b.path
+                .len()
+                .cmp(&a.path.len())
+                .then(a.creation_time.cmp(&b.creation_time))
+        });
+
+        let mut header = String::new();
+        for cookie in selected {
+            cookie.last_access_time = context.now;
+
+            if !header.is_empty() {
+                header.push_str("; ");
+            }
+            header.push_str(&cookie.name);
+            header.push('=');
+            header.push_str(&cookie.value);
+        }
+
+        Some(header)
+    }
+
+    /// Iterates over the stored cookies, in the order they were first created.
+    /// Cookies that have expired but have not been evicted yet are included.
+    pub fn iter(&self) -> impl Iterator<Item = &StoredCookie> {
+        self.cookies.iter()
+    }
+
+    /// Gets the number of stored cookies.
+    pub fn len(&self) -> usize {
+        self.cookies.len()
+    }
+
+    /// Determines whether the jar holds no cookies.
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+
+    fn count_domain(&self, domain: &str) -> usize {
+        self.cookies
+            .iter()
+            .filter(|cookie| cookie.domain == domain)
+            .count()
+    }
+
+    fn least_recently_accessed<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(&StoredCookie) -> bool,
+    {
+        // Ties go to the earliest stored cookie, which keeps the cookie just stored.
+        self.cookies
+            .iter()
+            .enumerate()
+            .filter(|(_, cookie)| predicate(cookie))
+            .min_by_key(|(idx, cookie)| (cookie.last_access_time, *idx))
+            .map(|(idx, _)| idx)
+    }
+
+    fn evict(&mut self, idx: usize, reason: EvictionReason) -> EvictedCookie {
+        EvictedCookie {
+            cookie: self.cookies.remove(idx),
+            reason,
+        }
+    }
+}
+
+impl CookieJarLimits {
+    /// Creates limits allowing at most `per_domain` cookies for each cookie domain, and at most
+    /// `total` cookies in the whole jar.
+    ///
+    /// Cookies are counted per canonicalized cookie domain, so host-only cookies and cookies
+    /// with a `Domain` attribute are counted towards the domain they are stored with.
+    pub fn new(per_domain: usize, total: usize) -> CookieJarLimits {
+        CookieJarLimits { per_domain, total }
+    }
+
+    /// Limits that never evict any cookies.
+    pub fn unlimited() -> CookieJarLimits {
+        CookieJarLimits::new(usize::MAX, usize::MAX)
+    }
+
+    /// Gets the maximum number of cookies for each cookie domain.
+    pub fn get_per_domain(&self) -> usize {
+        self.per_domain
+    }
+
+    /// Gets the maximum number of cookies in the whole jar.
+    pub fn get_total(&self) -> usize {
+        self.total
+    }
+}
+
+impl Default for CookieJarLimits {
+    fn default() -> CookieJarLimits {
+        CookieJarLimits::new(50, 3000)
+    }
+}
+
+impl StoreReport {
+    /// Determines whether the cookie was stored, as returned by `CookieJar::store`.
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+
+    /// Gets the cookies that were evicted, in the order they were evicted.
+    pub fn get_evicted(&self) -> &[EvictedCookie] {
+        &self.evicted
+    }
+
+    /// Takes the cookies that were evicted, in the order they were evicted.
+    pub fn into_evicted(self) -> Vec<EvictedCookie> {
+        self.evicted
+    }
+}
+
+impl EvictedCookie {
+    /// Gets the evicted cookie.
+    pub fn get_cookie(&self) -> &StoredCookie {
+        &self.cookie
+    }
+
+    /// Gets the reason the cookie was evicted.
+    pub fn get_reason(&self) -> EvictionReason {
+        self.reason
+    }
+
+    /// Takes the evicted cookie.
+    pub fn into_cookie(self) -> StoredCookie {
+        self.cookie
+    }
+}
+
+impl StoredCookie {
+    fn from_set_cookie(
+        set_cookie: &SetCookie,
+        url: &RequestUrl,
+        context: &RequestContext,
+    ) -> Option<StoredCookie> {
+        let mut max_age = None;
+        let mut expires = None;
+        let mut domain_attribute = None;
+        let mut path_attribute = None;
+        let mut secure_only = false;
+        let mut http_only = false;
+
+        for attribute in set_cookie.get_attributes() {
+            match *attribute {
+                SetCookieAttribute::Expires(time) => expires = Some(time),
+                SetCookieAttribute::MaxAge(seconds) => max_age = Some(seconds),
+                SetCookieAttribute::Domain(domain) => domain_attribute = Some(domain),
+                SetCookieAttribute::Path(path) => path_attribute = Some(path),
+                SetCookieAttribute::Secure => secure_only = true,
+                SetCookieAttribute::HttpOnly => http_only = true,
+                SetCookieAttribute::SameSite(_) | SetCookieAttribute::Partitioned => (),
+            }
+        }
+
+        let (persistent, expiry_time) = match (max_age, expires) {
+            (Some(seconds), _) if seconds <= 0 => (true, Some(UNIX_EPOCH)),
+            (Some(seconds), _) => {
+                let expiry_time = context
+                    .now
+                    .checked_add(Duration::from_secs(seconds as u64))
+                    .unwrap_or_else(|| latest_time_after(context.now));
+                (true, Some(expiry_time))
+            }
+            (None, Some(time)) => (true, Some(time)),
+            (None, None) => (false, None),
+        };
+
+        let request_host = canonicalize_host(url.get_host())?;
+        let (host_only, domain) = match domain_attribute {
+            Some(domain) => {
+                let domain = canonicalize_host(domain)?;
+                if is_public_suffix(&domain) {
+                    // A public suffix is only acceptable as the domain of the request host
+                    // itself, in which case it must not be shared with any other host.
+                    if domain != request_host {
+                        return None;
+                    }
+                    (true, domain)
+                } else if !domain_match(&request_host, &domain) {
+                    return None;
+                } else {
+                    (false, domain)
+                }
+            }
+            None => (true, request_host),
+        };
+
+        let path = match path_attribute {
+            Some(Some(path)) => path,
+            _ => default_path(url.get_path()),
+        };
+
+        if http_only && !context.http {
+            return None;
+        }
+
+        Some(StoredCookie {
+            name: set_cookie.get_name().to_owned(),
+            value: set_cookie.get_value().to_owned(),
+            expiry_time,
+            domain,
+            path: path.to_owned(),
+            creation_time: context.now,
+            last_access_time: context.now,
+            persistent,
+            host_only,
+            secure_only,
+            http_only,
+        })
+    }
+
+    fn matches_host(&self, canonicalized_host: &str) -> bool {
+        if self.host_only {
+            canonicalized_host == self.domain
+        } else {
+            domain_match(canonicalized_host, &self.domain)
+        }
+    }
+
+    /// Determines whether the cookie can be written in the strict `name=value` form.
+    fn is_writable(&self) -> bool {
+        is_cookie_name(&self.name) && is_cookie_value(&self.value)
+    }
+
+    fn same_identity(&self, other: &StoredCookie) -> bool {
+        self.name == other.name && self.domain == other.domain && self.path == other.path
+    }
+
+    /// Determines whether the cookie has expired at the given time.
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        match self.expiry_time {
+            Some(expiry_time) => expiry_time <= now,
+            None => false,
+        }
+    }
+
+    /// Gets the name and value of the cookie as a `Cookie`.
+    pub fn as_cookie(&self) -> Cookie<'_> {
+        Cookie::new(&self.name, &self.value)
+    }
+
+    /// Gets the name of the cookie.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the value of the cookie.
+    pub fn get_value(&self) -> &str {
+        &self.value
+    }
+
+    /// Gets the expiry time of the cookie, or `None` if it never expires.
+    /// Non-persistent cookies never expire, but do not outlive the session.
+    pub fn get_expiry_time(&self) -> Option<SystemTime> {
+        self.expiry_time
+    }
+
+    /// Gets the canonicalized domain of the cookie.
+    pub fn get_domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// Gets the path of the cookie.
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    /// Gets the time the cookie was first stored. Replacing a cookie keeps its creation time.
+    pub fn get_creation_time(&self) -> SystemTime {
+        self.creation_time
+    }
+
+    /// Gets the time the cookie was last stored or sent.
+    pub fn get_last_access_time(&self) -> SystemTime {
+        self.last_access_time
+    }
+
+    /// Determines whether the cookie had an `Expires` or a `Max-Age` attribute.
+    pub fn is_persistent(&self) -> bool {
+        self.persistent
+    }
+
+    /// Determines whether the cookie is only sent to the exact host that set it, which is the
+    /// case when it had no `Domain` attribute.
+    pub fn is_host_only(&self) -> bool {
+        self.host_only
+    }
+
+    /// Determines whether the cookie is only sent over secure protocols.
+    pub fn is_secure_only(&self) -> bool {
+        self.secure_only
+    }
+
+    /// Determines whether the cookie is hidden from non-HTTP APIs.
+    pub fn is_http_only(&self) -> bool {
+        self.http_only
+    }
+}
+
+impl RequestContext {
+    /// A context for cookies received or sent by an HTTP request, at the current time.
+    pub fn http() -> RequestContext {
+        RequestContext {
+            now: SystemTime::now(),
+            http: true,
+        }
+    }
+
+    /// A context for cookies accessed through a non-HTTP API, such as `document.cookie`,
+    /// at the current time.
+    pub fn non_http() -> RequestContext {
+        RequestContext {
+            now: SystemTime::now(),
+            http: false,
+        }
+    }
+
+    /// Replaces the current time with the given time.
+    pub fn at(mut self, now: SystemTime) -> RequestContext {
+        self.now = now;
+        self
+    }
+}
+
+/// Finds the latest time the platform can represent, for an expiry time past it, as
+/// [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-5.2.2) allows.
+fn latest_time_after(time: SystemTime) -> SystemTime {
+    let mut latest = time;
+    let mut step = Duration::MAX;
+    while step > Duration::ZERO {
+        match latest.checked_add(step) {
+            Some(later) => latest = later,
+            None => step /= 2,
+        }
+    }
+    latest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CookieJar, CookieJarLimits, EvictionReason, RequestContext};
+    use crate::{Cookie, RequestUrl, SetCookie};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    fn time(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    fn store(jar: &mut CookieJar, set_cookie: &str, url: &str, now: u64) -> bool {
+        jar.store(
+            &SetCookie::parse(set_cookie).unwrap(),
+            &RequestUrl::parse(url).unwrap(),
+            &RequestContext::http().at(time(now)),
+        )
+    }
+
+    #[test]
+    fn host_only_cookie() {
+        let mut jar = CookieJar::new();
+        assert!(store(&mut jar, "a=1", "http://WWW.Example.com/x/y", 1000));
+
+        let cookie = jar.iter().next().unwrap();
+        assert_eq!("a", cookie.get_name());
+        assert_eq!("1", cookie.get_value());
+        assert_eq!("www.example.com", cookie.get_domain());
+        assert_eq!("/x", cookie.get_path());
+        assert_eq!(time(1000), cookie.get_creation_time());
+        assert_eq!(time(1000), cookie.get_last_access_time());
+        assert_eq!(None, cookie.get_expiry_time());
+        assert!(cookie.is_host_only());
+        assert!(!cookie.is_persistent());
+        assert!(!cookie.is_secure_only());
+        assert!(!cookie.is_http_only());
+    }
+
+    #[test]
+    fn domain_cookie() {
+        let mut jar = CookieJar::new();
+        assert!(store(
+            &mut jar,
+            "a=1; Domain=.EXAMPLE.com; Path=/; Secure; HttpOnly",
+            "https://www.example.com/x/y",
+            1000
+        ));
+
+        let cookie = jar.iter().next().unwrap();
+        assert_eq!("example.com", cookie.get_domain());
+        assert_eq!("/", cookie.get_path());
+        assert!(!cookie.is_host_only());
+        assert!(cookie.is_secure_only());
+        assert!(cookie.is_http_only());
+    }
+
+    #[test]
+    fn domain_mismatch_ignored() {
+        let mut jar = CookieJar::new();
+        assert!(!store(
+            &mut jar,
+            "a=1; Domain=other.com",
+            "http://www.example.com/",
+            1000
+        ));
+        assert!(!store(
+            &mut jar,
+            "a=1; Domain=www.example.com",
+            "http://example.com/",
+            1000
+        ));
+        assert!(!store(
+            &mut jar,
+            "a=1; Domain=ample.com",
+            "http://example.com/",
+            1000
+        ));
+        assert!(jar.is_empty());
+    }
+
+    #[cfg(feature = "psl")]
+    #[test]
+    fn public_suffix_domain_ignored() {
+        let mut jar = CookieJar::new();
+        assert!(!store(
+            &mut jar,
+            "a=1; Domain=co.uk",
+            "http://evil.co.uk/",
+            1000
+        ));
+        assert!(!store(
+            &mut jar,
+            "a=1; Domain=.github.io",
+            "http://example.github.io/",
+            1000
+        ));
+        assert!(store(
+            &mut jar,
+            "a=1; Domain=example.co.uk",
+            "http://www.example.co.uk/",
+            1000
+        ));
+        assert_eq!(1, jar.len());
+    }
+
+    #[cfg(feature = "psl")]
+    #[test]
+    fn public_suffix_domain_of_request_host_is_host_only() {
+        let mut jar = CookieJar::new();
+        assert!(store(&mut jar, "a=1; Domain=co.uk", "http://co.uk/", 1000));
+
+        let cookie = jar.iter().next().unwrap();
+        assert_eq!("co.uk", cookie.get_domain());
+        assert!(cookie.is_host_only());
+    }
+
+    #[test]
+    fn invalid_path_uses_default_path() {
+        let mut jar = CookieJar::new();
+        assert!(store(
+            &mut jar,
+            "a=1; Path=/p; Path=relative",
+            "http://example.com/x/y",
+            1000
+        ));
+        assert_eq!("/x", jar.iter().next().unwrap().get_path());
+    }
+
+    #[test]
+    fn max_age_takes_precedence_over_expires() {
+        let mut jar = CookieJar::new();
+        assert!(store(
+            &mut jar,
+            "a=1; Max-Age=60; Expires=Sun, 06 Nov 2094 08:49:37 GMT",
+            "http://example.com/",
+            1000
+        ));
+
+        let cookie = jar.iter().next().unwrap();
+        assert!(cookie.is_persistent());
+        assert_eq!(Some(time(1060)), cookie.get_expiry_time());
+    }
+
+    #[test]
+    fn max_age_past_latest_time_is_clamped() {
+        let mut jar = CookieJar::new();
+        assert!(store(
+            &mut jar,
+            "a=1; Max-Age=9223372036854775807",
+            "http://example.com/",
+            1000
+        ));
+
+        let cookie = jar.iter().next().unwrap();
+        assert!(cookie.is_persistent());
+        let expiry_time = cookie.get_expiry_time().unwrap();
+        assert!(expiry_time > time(1000));
+        assert!(expiry_time.checked_add(Duration::from_nanos(1)).is_none());
+    }
+
+    #[test]
+    fn expires() {
+        let mut jar = CookieJar::new();
+        assert!(store(
+            &mut jar,
+            "a=1; Expires=Sun, 06 Nov 1994 08:49:37 GMT",
+            "http://example.com/",
+            1000
+        ));
+
+        let cookie = jar.iter().next().unwrap();
+        assert!(cookie.is_persistent());
+        assert_eq!(Some(time(784_111_777)), cookie.get_expiry_time());
+    }
+
+    #[test]
+    fn replace_keeps_creation_time() {
+        let mut jar = CookieJar::new();
+        assert!(store(&mut jar, "a=1", "http://example.com/", 1000));
+        assert!(store(&mut jar, "b=2", "http://example.com/", 1500));
+        assert!(store(&mut jar, "a=3", "http://example.com/", 2000));
+        assert_eq!(2, jar.len());
+
+        let cookie = jar.iter().find(|c| c.get_name() == "a").unwrap();
+        assert_eq!("3", cookie.get_value());
+        assert_eq!(time(1000), cookie.get_creation_time());
+        assert_eq!(time(2000), cookie.get_last_access_time());
+    }
+
+    #[test]
+    fn replace_keeps_position() {
+        let mut jar = CookieJar::new();
+        assert!(store(&mut jar, "a=1", "http://example.com/", 1000));
+        assert!(store(&mut jar, "b=2", "http://example.com/", 1500));
+        assert!(store(&mut jar, "c=3", "http://example.com/", 1600));
+        assert!(store(&mut jar, "a=4", "http://example.com/", 2000));
+        assert!(store(&mut jar, "b=5", "http://example.com/", 2100));
+
+        assert_eq!(vec!["a", "b", "c"], names(&jar));
+        assert_eq!(
+            vec!["4", "5", "3"],
+            jar.iter().map(|c| c.get_value()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn different_path_or_domain_not_replaced() {
+        let mut jar = CookieJar::new();
+        assert!(store(
+            &mut jar,
+            "a=1; Path=/",
+            "http://www.example.com/",
+            1000
+        ));
+        assert!(store(
+            &mut jar,
+            "a=2; Path=/x",
+            "http://www.example.com/",
+            1000
+        ));
+        assert!(store(
+            &mut jar,
+            "a=3; Path=/; Domain=example.com",
+            "http://www.example.com/",
+            1000
+        ));
+        assert_eq!(3, jar.len());
+    }
+
+    #[test]
+    fn expired_cookie_removes_existing() {
+        let mut jar = CookieJar::new();
+        assert!(store(&mut jar, "a=1", "http://example.com/", 1000));
+        assert!(store(
+            &mut jar,
+            "a=; Max-Age=0",
+            "http://example.com/",
+            2000
+        ));
+        assert!(jar.is_empty());
+
+        assert!(store(
+            &mut jar,
+            "a=1; Expires=Sun, 06 Nov 1994 08:49:37 GMT",
+            "http://example.com/",
+            784_111_777
+        ));
+        assert!(jar.is_empty());
+    }
+
+    #[test]
+    fn non_http_api() {
+        let mut jar = CookieJar::new();
+        let url = RequestUrl::parse("http://example.com/").unwrap();
+        let context = RequestContext::non_http().at(time(1000));
+
+        assert!(!jar.store(&SetCookie::parse("a=1; HttpOnly").unwrap(), &url, &context));
+        assert!(jar.is_empty());
+
+        assert!(store(
+            &mut jar,
+            "a=1; HttpOnly",
+            "http://example.com/",
+            1000
+        ));
+        assert!(!jar.store(&SetCookie::parse("a=2").unwrap(), &url, &context));
+        assert_eq!("1", jar.iter().next().unwrap().get_value());
+
+        assert!(jar.store(&SetCookie::parse("b=2").unwrap(), &url, &context));
+        assert_eq!(2, jar.len());
+    }
+
+    fn store_with_report(
+        jar: &mut CookieJar,
+        set_cookie: &str,
+        url: &str,
+        now: u64,
+    ) -> Vec<(String, EvictionReason)> {
+        let report = jar.store_with_report(
+            &SetCookie::parse(set_cookie).unwrap(),
+            &RequestUrl::parse(url).unwrap(),
+            &RequestContext::http().at(time(now)),
+        );
+        assert!(report.is_stored());
+
+        report
+            .get_evicted()
+            .iter()
+            .map(|evicted| {
+                (
+                    evicted.get_cookie().get_name().to_owned(),
+                    evicted.get_reason(),
+                )
+            })
+            .collect()
+    }
+
+    fn names(jar: &CookieJar) -> Vec<&str> {
+        jar.iter().map(|cookie| cookie.get_name()).collect()
+    }
+
+    #[test]
+    fn default_limits() {
+        let limits = CookieJar::new().get_limits();
+        assert_eq!(50, limits.get_per_domain());
+        assert_eq!(3000, limits.get_total());
+    }
+
+    #[test]
+    fn within_limits_nothing_evicted() {
+        let mut jar = CookieJar::with_limits(CookieJarLimits::new(2, 2));
+        assert!(store_with_report(&mut jar, "a=1", "http://example.com/", 1000).is_empty());
+        assert!(store_with_report(&mut jar, "b=1", "http://example.com/", 1000).is_empty());
+        assert!(store_with_report(&mut jar, "b=2", "http://example.com/", 1000).is_empty());
+        assert_eq!(vec!["a", "b"], names(&jar));
+    }
+
+    #[test]
+    fn domain_limit_evicts_least_recently_accessed() {
+        let mut jar = CookieJar::with_limits(CookieJarLimits::new(2, 10));
+        store(&mut jar, "a=1", "http://example.com/", 1000);
+        store(&mut jar, "b=1", "http://example.com/", 1001);
+        store(&mut jar, "c=1", "http://other.com/", 1002);
+        header(&mut jar, "http://example.com/", 1003);
+        store(&mut jar, "b=2", "http://example.com/", 1004);
+
+        assert_eq!(
+            vec![("a".to_owned(), EvictionReason::DomainLimit)],
+            store_with_report(&mut jar, "d=1", "http://example.com/", 1005)
+        );
+        assert_eq!(vec!["b", "c", "d"], names(&jar));
+    }
+
+    #[test]
+    fn domain_limit_counts_domain_cookies_separately() {
+        let mut jar = CookieJar::with_limits(CookieJarLimits::new(1, 10));
+        store(&mut jar, "a=1", "http://www.example.com/", 1000);
+
+        assert!(store_with_report(
+            &mut jar,
+            "b=1; Domain=example.com",
+            "http://www.example.com/",
+            1001
+        )
+        .is_empty());
+        assert_eq!(2, jar.len());
+    }
+
+    #[test]
+    fn total_limit_evicts_least_recently_accessed() {
+        let mut jar = CookieJar::with_limits(CookieJarLimits::new(10, 2));
+        store(&mut jar, "a=1", "http://a.com/", 1000);
+        store(&mut jar, "b=1", "http://b.com/", 1001);
+        header(&mut jar, "http://a.com/", 1002);
+
+        assert_eq!(
+            vec![("b".to_owned(), EvictionReason::TotalLimit)],
+            store_with_report(&mut jar, "c=1", "http://c.com/", 1003)
+        );
+        assert_eq!(vec!["a", "c"], names(&jar));
+    }
+
+    #[test]
+    fn expired_cookies_evicted_first() {
+        let mut jar = CookieJar::with_limits(CookieJarLimits::new(2, 10));
+        store(&mut jar, "a=1", "http://example.com/", 1000);
+        store(&mut jar, "b=1; Max-Age=10", "http://example.com/", 1001);
+        store(&mut jar, "c=1; Max-Age=10", "http://other.com/", 1002);
+
+        assert_eq!(
+            vec![
+                ("b".to_owned(), EvictionReason::Expired),
+                ("c".to_owned(), EvictionReason::Expired)
+            ],
+            store_with_report(&mut jar, "d=1", "http://example.com/", 2000)
+        );
+        assert_eq!(vec!["a", "d"], names(&jar));
+    }
+
+    #[test]
+    fn expired_cookies_evicted_before_total_limit() {
+        let mut jar = CookieJar::with_limits(CookieJarLimits::new(10, 2));
+        store(&mut jar, "a=1", "http://a.com/", 1000);
+        store(&mut jar, "b=1; Max-Age=10", "http://b.com/", 1001);
+
+        assert_eq!(
+            vec![("b".to_owned(), EvictionReason::Expired)],
+            store_with_report(&mut jar, "c=1", "http://c.com/", 2000)
+        );
+        assert_eq!(vec!["a", "c"], names(&jar));
+    }
+
+    #[test]
+    fn unlimited() {
+        let mut jar = CookieJar::with_limits(CookieJarLimits::unlimited());
+        for i in 0..100 {
+            let set_cookie = format!("c{}=1", i);
+            assert!(
+                store_with_report(&mut jar, &set_cookie, "http://example.com/", 1000).is_empty()
+            );
+        }
+        assert_eq!(100, jar.len());
+    }
+
+    #[test]
+    fn evict_expired() {
+        let mut jar = CookieJar::new();
+        store(&mut jar, "a=1; Max-Age=10", "http://example.com/", 1000);
+        store(&mut jar, "b=1", "http://example.com/", 1000);
+        store(&mut jar, "c=1; Max-Age=20", "http://example.com/", 1000);
+
+        let evicted = jar.evict_expired(time(1015));
+        assert_eq!(1, evicted.len());
+        assert_eq!("a", evicted[0].get_cookie().get_name());
+        assert_eq!(EvictionReason::Expired, evicted[0].get_reason());
+        assert_eq!(vec!["b", "c"], names(&jar));
+
+        assert!(jar.evict_expired(time(1015)).is_empty());
+    }
+
+    fn header(jar: &mut CookieJar, url: &str, now: u64) -> Option<String> {
+        jar.cookie_header_for(
+            &RequestUrl::parse(url).unwrap(),
+            &RequestContext::http().at(time(now)),
+        )
+    }
+
+    #[test]
+    fn header_empty_jar() {
+        let mut jar = CookieJar::new();
+        assert_eq!(None, header(&mut jar, "http://example.com/", 1000));
+    }
+
+    #[test]
+    fn header_host_only() {
+        let mut jar = CookieJar::new();
+        assert!(store(&mut jar, "a=1", "http://example.com/", 1000));
+
+        assert_eq!(
+            Some("a=1"),
+            header(&mut jar, "http://EXAMPLE.com/", 1000).as_deref()
+        );
+        assert_eq!(None, header(&mut jar, "http://www.example.com/", 1000));
+    }
+
+    #[test]
+    fn header_domain() {
+        let mut jar = CookieJar::new();
+        assert!(store(
+            &mut jar,
+            "a=1; Domain=example.com",
+            "http://example.com/",
+            1000
+        ));
+
+        assert_eq!(
+            Some("a=1"),
+            header(&mut jar, "http://example.com/", 1000).as_deref()
+        );
+        assert_eq!(
+            Some("a=1"),
+            header(&mut jar, "http://a.b.example.com/", 1000).as_deref()
+        );
+        assert_eq!(None, header(&mut jar, "http://notexample.com/", 1000));
+    }
+
+    #[test]
+    fn header_path() {
+        let mut jar = CookieJar::new();
+        assert!(store(
+            &mut jar,
+            "a=1; Path=/foo",
+            "http://example.com/",
+            1000
+        ));
+
+        assert_eq!(
+            Some("a=1"),
+            header(&mut jar, "http://example.com/foo", 1000).as_deref()
+        );
+        assert_eq!(
+            Some("a=1"),
+            header(&mut jar, "http://example.com/foo/bar?x", 1000).as_deref()
+        );
+        assert_eq!(None, header(&mut jar, "http://example.com/foobar", 1000));
+        assert_eq!(None, header(&mut jar, "http://example.com/", 1000));
+        assert_eq!(None, header(&mut jar, "http://example.com", 1000));
+    }
+
+    #[test]
+    fn header_secure() {
+        let mut jar = CookieJar::new();
+        assert!(store(&mut jar, "a=1; Secure", "https://example.com/", 1000));
+
+        assert_eq!(
+            Some("a=1"),
+            header(&mut jar, "https://example.com/", 1000).as_deref()
+        );
+        assert_eq!(None, header(&mut jar, "http://example.com/", 1000));
+    }
+
+    #[test]
+    fn header_http_only() {
+        let mut jar = CookieJar::new();
+        assert!(store(
+            &mut jar,
+            "a=1; HttpOnly",
+            "http://example.com/",
+            1000
+        ));
+        assert!(store(&mut jar, "b=2", "http://example.com/", 1000));
+
+        let url = RequestUrl::parse("http://example.com/").unwrap();
+        assert_eq!(
+            Some("b=2"),
+            jar.cookie_header_for(&url, &RequestContext::non_http().at(time(1000)))
+                .as_deref()
+        );
+        assert_eq!(
+            Some("a=1; b=2"),
+            header(&mut jar, "http://example.com/", 1000).as_deref()
+        );
+    }
+
+    #[test]
+    fn header_order() {
+        let mut jar = CookieJar::new();
+        assert!(store(&mut jar, "a=1; Path=/", "http://example.com/", 1003));
+        assert!(store(
+            &mut jar,
+            "b=2; Path=/x/y",
+            "http://example.com/",
+            1002
+        ));
+        assert!(store(&mut jar, "c=3; Path=/x", "http://example.com/", 1001));
+        assert!(store(&mut jar, "d=4; Path=/", "http://example.com/", 1000));
+        assert!(store(&mut jar, "e=5; Path=/x", "http://example.com/", 1004));
+
+        assert_eq!(
+            Some("b=2; c=3; e=5; d=4; a=1"),
+            header(&mut jar, "http://example.com/x/y/z", 2000).as_deref()
+        );
+    }
+
+    #[test]
+    fn header_updates_last_access_time() {
+        let mut jar = CookieJar::new();
+        assert!(store(&mut jar, "a=1; Path=/x", "http://example.com/", 1000));
+        assert!(store(&mut jar, "b=2; Path=/y", "http://example.com/", 1000));
+
+        assert_eq!(
+            Some("a=1"),
+            header(&mut jar, "http://example.com/x", 2000).as_deref()
+        );
+
+        let a = jar.iter().find(|c| c.get_name() == "a").unwrap();
+        assert_eq!(time(2000), a.get_last_access_time());
+        assert_eq!(time(1000), a.get_creation_time());
+
+        let b = jar.iter().find(|c| c.get_name() == "b").unwrap();
+        assert_eq!(time(1000), b.get_last_access_time());
+    }
+
+    #[test]
+    fn header_skips_and_evicts_expired() {
+        let mut jar = CookieJar::new();
+        assert!(store(
+            &mut jar,
+            "a=1; Max-Age=10",
+            "http://example.com/",
+            1000
+        ));
+        assert!(store(&mut jar, "b=2", "http://example.com/", 1000));
+
+        assert_eq!(
+            Some("a=1; b=2"),
+            header(&mut jar, "http://example.com/", 1009).as_deref()
+        );
+        assert_eq!(
+            Some("b=2"),
+            header(&mut jar, "http://example.com/", 1010).as_deref()
+        );
+        assert_eq!(1, jar.len());
+    }
+
+    #[test]
+    fn header_parses_back() {
+        let mut jar = CookieJar::new();
+        assert!(store(&mut jar, "a=x=1", "http://example.com/", 1000));
+        assert!(store(&mut jar, "b=\"q\"", "http://example.com/", 1001));
+        assert!(store(&mut jar, "c=", "http://example.com/", 1002));
+
+        let header = header(&mut jar, "http://example.com/", 2000).unwrap();
+        let parsed_cookies = Cookie::parse(&header).unwrap();
+        assert_eq!(3, parsed_cookies.len());
+        assert_eq!(
+            ("a", "x=1"),
+            (parsed_cookies[0].get_name(), parsed_cookies[0].get_value())
+        );
+        assert_eq!(
+            ("b", "q"),
+            (parsed_cookies[1].get_name(), parsed_cookies[1].get_value())
+        );
+        assert_eq!(
+            ("c", ""),
+            (parsed_cookies[2].get_name(), parsed_cookies[2].get_value())
+        );
+    }
+
+    #[test]
+    fn header_skips_unwritable_values() {
+        let mut jar = CookieJar::new();
+        assert!(store(&mut jar, "a=1", "http://example.com/", 1000));
+        assert!(store(&mut jar, "b=x y", "http://example.com/", 1001));
+        assert!(store(&mut jar, "c=1,2", "http://example.com/", 1002));
+        assert!(store(&mut jar, "d=4", "http://example.com/", 1003));
+        assert!(store(&mut jar, "e=\"x y\"", "http://example.com/", 1004));
+        assert!(store(&mut jar, "f=\"\"", "http://example.com/", 1005));
+
+        let written = header(&mut jar, "http://example.com/", 2000).unwrap();
+        assert_eq!("a=1; d=4", written);
+        assert_eq!(2, Cookie::parse(&written).unwrap().len());
+
+        let b = jar.iter().find(|c| c.get_name() == "b").unwrap();
+        assert_eq!(time(1001), b.get_last_access_time());
+
+        let mut jar = CookieJar::new();
+        assert!(store(&mut jar, "b=x y", "http://example.com/", 1000));
+        assert_eq!(None, header(&mut jar, "http://example.com/", 2000));
+    }
+
+    #[test]
+    fn every_header_parses_back() {
+        const SET_COOKIES: &[&str] = &[
+            "a=1",
+            "a=",
+            "=v",
+            "a==",
+            "a=x=y",
+            "a=\"q\"",
+            "a=\"=\"",
+            "a=\"\"",
+            "a=\"",
+            "a=x\"y",
+            "a=\"x y\"",
+            "a=x y",
+            "a=1,2",
+            "a=x\\y",
+            "a=\u{e9}",
+            "a(b=1",
+            "a b=1",
+            "\u{e9}=1",
+        ];
+        let url = RequestUrl::parse("http://example.com/").unwrap();
+        let context = RequestContext::http().at(time(1000));
+
+        for input in SET_COOKIES {
+            let set_cookie = match SetCookie::parse(input) {
+                Some(set_cookie) => set_cookie,
+                None => continue,
+            };
+
+            let mut jar = CookieJar::new();
+            jar.store(&set_cookie, &url, &context);
+
+            if let Some(written) = jar.cookie_header_for(&url, &context) {
+                let parsed_cookies = Cookie::parse(&written)
+                    .unwrap_or_else(|err| panic!("{:?} -> {:?}: {}", input, written, err));
+                assert_eq!(1, parsed_cookies.len(), "{:?}", written);
+            }
+        }
+    }
+
+    #[test]
+    fn as_cookie() {
+        let mut jar = CookieJar::new();
+        assert!(store(&mut jar, "a=1", "http://example.com/", 1000));
+
+        let cookie = jar.iter().next().unwrap().as_cookie();
+        assert_eq!("a", cookie.get_name());
+        assert_eq!("1", cookie.get_value());
+    }
+}
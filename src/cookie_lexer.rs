@@ -1,23 +1,31 @@
+use super::token_class::{char_token_class, CharTokenClass};
 use std::fmt::{Display, Error as FormatterError, Formatter};
 
-const COOKIE_LEXER_ERROR_DESCRIPTION: &'static str = "Cookie Lexer Error";
+const COOKIE_LEXER_ERROR_DESCRIPTION: &str = "Cookie Lexer Error";
 
-#[derive(Debug)]
-pub struct CookieLexerError;
-
-impl PartialEq<CookieLexerError> for CookieLexerError {
-    fn eq(&self, _other: &CookieLexerError) -> bool {
-        false
-    }
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CookieLexerError {
+    position: usize,
+    character: char,
+}
 
-    fn ne(&self, _other: &CookieLexerError) -> bool {
-        false
+impl CookieLexerError {
+    pub(crate) fn new(position: usize, character: char) -> CookieLexerError {
+        CookieLexerError {
+            position,
+            character,
+        }
     }
 }
 
 impl Display for CookieLexerError {
     fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
-        f.write_str(COOKIE_LEXER_ERROR_DESCRIPTION)
+        f.write_str(COOKIE_LEXER_ERROR_DESCRIPTION)?;
+        write!(
+            f,
+            ": unexpected character {:?} at byte {}",
+            self.character, self.position
+        )
     }
 }
 
@@ -132,7 +140,7 @@ impl<'input> CookieLexer<'input> {
     pub fn new(data: &'input str) -> CookieLexer<'input> {
         CookieLexer {
             cursor: 0,
-            data: data,
+            data,
             char_indices: data.char_indices().collect(),
         }
     }
@@ -142,12 +150,9 @@ impl<'input> CookieLexer<'input> {
     }
 
     fn get_next_token(&mut self) -> Option<Result<(usize, CookieToken, usize), CookieLexerError>> {
-        let cursor_str = match self.substr_at_cursor() {
-            Some(val) => val,
-            None => return None,
-        };
+        let cursor_str = self.substr_at_cursor()?;
 
-        if cursor_str.len() == 0 {
+        if cursor_str.is_empty() {
             return None;
         }
 
@@ -173,8 +178,8 @@ impl<'input> CookieLexer<'input> {
         let mut can_be_token = true;
         let mut token_end_idx = 0_usize;
 
-        for (_, cursor_char) in self.char_indices[self.cursor..].iter() {
-            match CookieLexer::char_token_class(*cursor_char) {
+        for (cursor_char_idx, cursor_char) in self.char_indices[self.cursor..].iter() {
+            match char_token_class(*cursor_char) {
                 CharTokenClass::TokenOrCookieOctets => {
                     token_end_idx += cursor_char.len_utf8();
                 }
@@ -186,7 +191,7 @@ impl<'input> CookieLexer<'input> {
                     if token_end_idx > 0_usize {
                         break;
                     } else {
-                        return None;
+                        return Some(Err(CookieLexerError::new(*cursor_char_idx, *cursor_char)));
                     }
                 }
             };
@@ -208,33 +213,6 @@ impl<'input> CookieLexer<'input> {
             None
         }
     }
-
-    fn char_token_class(c: char) -> CharTokenClass {
-        match c {
-            '\x21'
-            | '\x23'..='\x27'
-            | '\x2a'
-            | '\x2b'
-            | '\x2d'
-            | '\x2e'
-            | '\x30'..='\x39'
-            | '\x41'..='\x5a'
-            | '\x5e'..='\x7a'
-            | '\x7c'
-            | '\x7e' => CharTokenClass::TokenOrCookieOctets,
-            '\x28'
-            | '\x29'
-            | '\x2f'
-            | '\x3a'
-            | '\x3c'
-            | '\x3e'..='\x40'
-            | '\x5b'
-            | '\x5d'
-            | '\x7b'
-            | '\x7d' => CharTokenClass::CookieOctets, // excludes = (x3d)
-            _ => CharTokenClass::None,
-        }
-    }
 }
 
 impl<'input> Display for CookieLexer<'input> {
@@ -246,12 +224,6 @@ impl<'input> Display for CookieLexer<'input> {
     }
 }
 
-enum CharTokenClass {
-    None,
-    CookieOctets,
-    TokenOrCookieOctets,
-}
-
 impl<'input> Iterator for CookieLexer<'input> {
     type Item = Result<(usize, CookieToken, usize), CookieLexerError>;
 
@@ -266,10 +238,7 @@ mod matching {
     }
 
     pub fn is_whitespace_char(c: char) -> bool {
-        match c {
-            '\x09' | '\x20' => true,
-            _ => false,
-        }
+        matches!(c, '\x09' | '\x20')
     }
 
     #[cfg(test)]
@@ -309,7 +278,7 @@ mod matching {
 mod tests {
     #[cfg(test)]
     mod get_next_token {
-        use super::super::{CookieLexer, CookieToken};
+        use super::super::{CookieLexer, CookieLexerError, CookieToken};
 
         #[test]
         fn equals() {
@@ -357,6 +326,29 @@ mod tests {
             );
         }
 
+        #[test]
+        fn invalid_char() {
+            assert_eq!(
+                Some(Err(CookieLexerError::new(0, ','))),
+                CookieLexer::new(",").get_next_token()
+            );
+        }
+
+        #[test]
+        fn token_and_invalid_char() {
+            let mut lexer = CookieLexer::new("abc\\");
+
+            assert_eq!(
+                Some(Ok((0, CookieToken::TokenOrCookieOctets, 3))),
+                lexer.get_next_token()
+            );
+
+            assert_eq!(
+                Some(Err(CookieLexerError::new(3, '\\'))),
+                lexer.get_next_token()
+            );
+        }
+
         #[test]
         fn cookie_octets() {
             assert_eq!(
@@ -0,0 +1,421 @@
+use super::{Cookie, Error};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{Display, Error as FormatterError, Formatter};
+
+const DUPLICATE_NAME_ERROR_DESCRIPTION: &str = "Duplicate Name Error";
+
+/// Parsed cookies indexed by name, keeping the order of the cookie string.
+///
+/// A cookie string may hold several cookies with the same name, such as cookies set for
+/// different paths. Which one a lookup returns is chosen by the `DuplicatePolicy`, and names
+/// are matched according to the `NameMatching`.
+///
+/// # Examples
+///
+/// ```
+/// use basic_cookies::{Cookie, CookieMap};
+///
+/// let cookies = Cookie::parse("SID=new; lang=en-US; SID=old").unwrap();
+/// let map = CookieMap::new(cookies);
+///
+/// assert_eq!("new", map.get("SID").unwrap().get_value());
+/// assert_eq!(2, map.get_all("SID").count());
+/// assert!(map.contains("lang"));
+/// assert!(!map.contains("theme"));
+///
+/// let names = map.iter().map(|cookie| cookie.get_name()).collect::<Vec<&str>>();
+/// assert_eq!(vec!["SID", "lang"], names);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CookieMap<'a> {
+    cookies: Vec<Cookie<'a>>,
+    /// The indices of the cookies of each name, in the order of the cookie string.
+    indices: HashMap<Cow<'a, str>, Vec<usize>>,
+    /// The indices of the cookies returned by lookups, in the order of the cookie string.
+    selected: Vec<usize>,
+    duplicate_policy: DuplicatePolicy,
+    name_matching: NameMatching,
+}
+
+/// Selects which of the cookies sharing a name `CookieMap` lookups return.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DuplicatePolicy {
+    /// Returns the first cookie. User agents send cookies with longer paths first, as
+    /// [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-5.4) recommends, so this is
+    /// the cookie with the most specific path.
+    #[default]
+    FirstWins,
+    /// Returns the last cookie.
+    LastWins,
+    /// Fails to create the map with an `Error::DuplicateNameError`.
+    Reject,
+}
+
+/// Selects how `CookieMap` compares cookie names.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NameMatching {
+    /// Names match if they are identical, as
+    /// [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-4.1.1) defines them.
+    #[default]
+    CaseSensitive,
+    /// Names match if they only differ in the case of ASCII letters.
+    AsciiCaseInsensitive,
+}
+
+impl<'a> CookieMap<'a> {
+    /// Indexes the cookies by name, returning the first cookie of each name, and matching names
+    /// case-sensitively.
+    pub fn new(cookies: Vec<Cookie<'a>>) -> CookieMap<'a> {
+        match CookieMap::with_options(
+            cookies,
+            DuplicatePolicy::FirstWins,
+            NameMatching::CaseSensitive,
+        ) {
+            Ok(map) => map,
+            Err(_) => unreachable!("only DuplicatePolicy::Reject fails"),
+        }
+    }
+
+    /// Indexes the cookies by name, using the given `DuplicatePolicy` and `NameMatching`.
+    ///
+    /// With `DuplicatePolicy::Reject`, fails with an `Error::DuplicateNameError` for the first
+    /// cookie whose name matches an earlier one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::{Cookie, CookieMap, DuplicatePolicy, Error, NameMatching};
+    ///
+    /// let cookies = Cookie::parse("lang=en; Lang=fr").unwrap();
+    ///
+    /// let map = CookieMap::with_options(
+    ///     cookies.clone(),
+    ///     DuplicatePolicy::LastWins,
+    ///     NameMatching::AsciiCaseInsensitive,
+    /// )
+    /// .unwrap();
+    /// assert_eq!("fr", map.get("LANG").unwrap().get_value());
+    ///
+    /// match CookieMap::with_options(
+    ///     cookies,
+    ///     DuplicatePolicy::Reject,
+    ///     NameMatching::AsciiCaseInsensitive,
+    /// ) {
+    ///     Err(Error::DuplicateNameError(err)) => {
+    ///         assert_eq!("Lang", err.name());
+    ///         assert_eq!(1, err.index());
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn with_options(
+        cookies: Vec<Cookie<'a>>,
+        duplicate_policy: DuplicatePolicy,
+        name_matching: NameMatching,
+    ) -> Result<CookieMap<'a>, Error> {
+        let mut indices: HashMap<Cow<'a, str>, Vec<usize>> = HashMap::new();
+        for (idx, cookie) in cookies.iter().enumerate() {
+            let same_name = indices
+                .entry(normalize(cookie.get_name(), name_matching))
+                .or_default();
+            if duplicate_policy == DuplicatePolicy::Reject && !same_name.is_empty() {
+                return Err(DuplicateNameError::new(cookie.get_name(), idx).into_error());
+            }
+
+            same_name.push(idx);
+        }
+
+        let mut selected = indices
+            .values()
+            .map(|same_name| select(same_name, duplicate_policy))
+            .collect::<Vec<usize>>();
+        selected.sort_unstable();
+
+        Ok(CookieMap {
+            cookies,
+            indices,
+            selected,
+            duplicate_policy,
+            name_matching,
+        })
+    }
+
+    /// Gets the cookie with the given name, chosen by the `DuplicatePolicy` if there are several.
+    pub fn get(&self, name: &str) -> Option<&Cookie<'a>> {
+        self.same_name(name)
+            .map(|same_name| &self.cookies[select(same_name, self.duplicate_policy)])
+    }
+
+    /// Gets every cookie with the given name, in the order of the cookie string.
+    pub fn get_all<'b>(&'b self, name: &str) -> impl Iterator<Item = &'b Cookie<'a>> {
+        self.same_name(name)
+            .unwrap_or_default()
+            .iter()
+            .map(move |idx| &self.cookies[*idx])
+    }
+
+    /// Determines whether there is a cookie with the given name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.same_name(name).is_some()
+    }
+
+    /// Iterates over the cookies lookups return, one for each name, in the order of the cookie
+    /// string.
+    pub fn iter<'b>(&'b self) -> impl Iterator<Item = &'b Cookie<'a>> {
+        self.selected.iter().map(move |idx| &self.cookies[*idx])
+    }
+
+    /// Gets the number of distinct cookie names.
+    pub fn len(&self) -> usize {
+        self.selected.len()
+    }
+
+    /// Determines whether there are no cookies.
+    pub fn is_empty(&self) -> bool {
+        self.selected.is_empty()
+    }
+
+    /// Gets every cookie, including those with the same name as another, in the order of the
+    /// cookie string.
+    pub fn get_cookies(&self) -> &[Cookie<'a>] {
+        &self.cookies
+    }
+
+    /// Gets the policy choosing between cookies with the same name.
+    pub fn get_duplicate_policy(&self) -> DuplicatePolicy {
+        self.duplicate_policy
+    }
+
+    /// Gets how cookie names are compared.
+    pub fn get_name_matching(&self) -> NameMatching {
+        self.name_matching
+    }
+
+    /// Takes every cookie, in the order of the cookie string.
+    pub fn into_cookies(self) -> Vec<Cookie<'a>> {
+        self.cookies
+    }
+
+    fn same_name(&self, name: &str) -> Option<&[usize]> {
+        self.indices
+            .get(&*normalize(name, self.name_matching))
+            .map(Vec::as_slice)
+    }
+}
+
+/// Gets the key a name is indexed by, only allocating for case-insensitive names with
+/// uppercase letters.
+fn normalize(name: &str, name_matching: NameMatching) -> Cow<'_, str> {
+    match name_matching {
+        NameMatching::AsciiCaseInsensitive if name.bytes().any(|b| b.is_ascii_uppercase()) => {
+            Cow::Owned(name.to_ascii_lowercase())
+        }
+        _ => Cow::Borrowed(name),
+    }
+}
+
+/// Selects the index of the cookie lookups return among the indices of cookies with the same
+/// name, which are never empty.
+fn select(same_name: &[usize], duplicate_policy: DuplicatePolicy) -> usize {
+    match duplicate_policy {
+        DuplicatePolicy::LastWins => same_name[same_name.len() - 1],
+        DuplicatePolicy::FirstWins | DuplicatePolicy::Reject => same_name[0],
+    }
+}
+
+/// A cookie with the same name as an earlier one, when creating a `CookieMap` with
+/// `DuplicatePolicy::Reject`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateNameError {
+    name: String,
+    index: usize,
+}
+
+impl DuplicateNameError {
+    fn new(name: &str, index: usize) -> DuplicateNameError {
+        DuplicateNameError {
+            name: name.to_owned(),
+            index,
+        }
+    }
+
+    /// Gets the name of the cookie, as it appeared in the cookie string.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the index of the cookie among the cookies the map was created with.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub(crate) fn into_error(self) -> Error {
+        Error::DuplicateNameError(self)
+    }
+}
+
+impl Display for DuplicateNameError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.write_str(DUPLICATE_NAME_ERROR_DESCRIPTION)?;
+        write!(
+            f,
+            ": cookie {} has the same name as an earlier cookie, {:?}",
+            self.index, self.name
+        )
+    }
+}
+
+impl std::error::Error for DuplicateNameError {
+    fn description(&self) -> &str {
+        DUPLICATE_NAME_ERROR_DESCRIPTION
+    }
+
+    fn cause(&self) -> Option<&dyn std::error::Error> {
+        None
+    }
+
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CookieMap, DuplicatePolicy, NameMatching};
+    use crate::{Cookie, Error};
+
+    const COOKIE_STR: &str = "a=1; B=2; a=3; b=4; =5";
+
+    fn map(duplicate_policy: DuplicatePolicy, name_matching: NameMatching) -> CookieMap<'static> {
+        CookieMap::with_options(
+            Cookie::parse(COOKIE_STR).unwrap(),
+            duplicate_policy,
+            name_matching,
+        )
+        .unwrap()
+    }
+
+    fn values<'a, I: Iterator<Item = &'a Cookie<'a>>>(cookies: I) -> Vec<&'a str> {
+        cookies.map(|cookie| cookie.get_value()).collect()
+    }
+
+    #[test]
+    fn first_wins() {
+        let map = CookieMap::new(Cookie::parse(COOKIE_STR).unwrap());
+        assert_eq!(DuplicatePolicy::FirstWins, map.get_duplicate_policy());
+        assert_eq!(NameMatching::CaseSensitive, map.get_name_matching());
+
+        assert_eq!("1", map.get("a").unwrap().get_value());
+        assert_eq!("2", map.get("B").unwrap().get_value());
+        assert_eq!("4", map.get("b").unwrap().get_value());
+        assert_eq!("5", map.get("").unwrap().get_value());
+        assert!(map.get("A").is_none());
+
+        assert_eq!(vec!["1", "2", "4", "5"], values(map.iter()));
+        assert_eq!(4, map.len());
+    }
+
+    #[test]
+    fn last_wins() {
+        let map = map(DuplicatePolicy::LastWins, NameMatching::CaseSensitive);
+        assert_eq!("3", map.get("a").unwrap().get_value());
+        assert_eq!(vec!["2", "3", "4", "5"], values(map.iter()));
+    }
+
+    #[test]
+    fn reject() {
+        let map = CookieMap::with_options(
+            Cookie::parse("a=1; A=2").unwrap(),
+            DuplicatePolicy::Reject,
+            NameMatching::CaseSensitive,
+        )
+        .unwrap();
+        assert_eq!("1", map.get("a").unwrap().get_value());
+
+        match CookieMap::with_options(
+            Cookie::parse(COOKIE_STR).unwrap(),
+            DuplicatePolicy::Reject,
+            NameMatching::CaseSensitive,
+        ) {
+            Err(Error::DuplicateNameError(err)) => {
+                assert_eq!("a", err.name());
+                assert_eq!(2, err.index());
+                assert_eq!(
+                    "Cookie Parsing Error: Duplicate Name Error: cookie 2 has the same name \
+                     as an earlier cookie, \"a\"",
+                    Error::DuplicateNameError(err).to_string()
+                );
+            }
+            res => panic!("expected duplicate name error, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn reject_case_insensitive() {
+        match CookieMap::with_options(
+            Cookie::parse(COOKIE_STR).unwrap(),
+            DuplicatePolicy::Reject,
+            NameMatching::AsciiCaseInsensitive,
+        ) {
+            Err(Error::DuplicateNameError(err)) => {
+                assert_eq!("a", err.name());
+                assert_eq!(2, err.index());
+            }
+            res => panic!("expected duplicate name error, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let map = map(
+            DuplicatePolicy::FirstWins,
+            NameMatching::AsciiCaseInsensitive,
+        );
+        assert_eq!("2", map.get("b").unwrap().get_value());
+        assert_eq!("1", map.get("A").unwrap().get_value());
+        assert_eq!(vec!["2", "4"], values(map.get_all("b")));
+        assert_eq!(vec!["1", "2", "5"], values(map.iter()));
+        assert_eq!(3, map.len());
+
+        // Only ASCII letters are folded.
+        let map = CookieMap::with_options(
+            vec![Cookie::new("é", "1")],
+            DuplicatePolicy::FirstWins,
+            NameMatching::AsciiCaseInsensitive,
+        )
+        .unwrap();
+        assert!(map.contains("é"));
+        assert!(!map.contains("É"));
+    }
+
+    #[test]
+    fn get_all() {
+        let map = map(DuplicatePolicy::LastWins, NameMatching::CaseSensitive);
+        assert_eq!(vec!["1", "3"], values(map.get_all("a")));
+        assert_eq!(vec!["2"], values(map.get_all("B")));
+        assert_eq!(0, map.get_all("c").count());
+    }
+
+    #[test]
+    fn contains() {
+        let map = map(DuplicatePolicy::FirstWins, NameMatching::CaseSensitive);
+        assert!(map.contains("a"));
+        assert!(map.contains(""));
+        assert!(!map.contains("c"));
+    }
+
+    #[test]
+    fn cookies() {
+        let map = map(DuplicatePolicy::FirstWins, NameMatching::CaseSensitive);
+        assert_eq!(5, map.get_cookies().len());
+        assert_eq!(
+            Cookie::parse(COOKIE_STR).unwrap(),
+            map.clone().into_cookies()
+        );
+
+        let map = CookieMap::new(Vec::new());
+        assert!(map.is_empty());
+        assert_eq!(0, map.iter().count());
+    }
+}
@@ -0,0 +1,660 @@
+use super::token_class::{char_token_class, CharTokenClass};
+use super::{ExpectedToken, ExpectedTokens, NonAsciiPolicy, ParseError, ParseErrorKind};
+use std::ops::Range;
+
+/// The position of the parser within the strict cookie string grammar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// Optional whitespace before the first cookie pair.
+    LeadingWhitespace,
+    /// The start of a cookie pair, after a `"; "` separator.
+    PairStart,
+    /// An unquoted run of cookie-octets at the start of a pair, which is the name if it is a
+    /// token followed by `=`, or otherwise a value without a name.
+    Name { is_token: bool },
+    /// A quoted value without a name, after the opening quote.
+    QuotedNamelessValue { is_empty: bool },
+    /// The start of a value, after the `=`.
+    ValueStart,
+    /// An unquoted value.
+    Value,
+    /// A quoted value, after the opening quote.
+    QuotedValue { is_empty: bool },
+    /// The end of a cookie pair, after the closing quote of a value.
+    PairEnd,
+    /// A `;`, before the space that has to follow it.
+    Semicolon,
+    /// Optional whitespace after the last cookie pair.
+    TrailingWhitespace,
+}
+
+/// The classes of bytes the grammar distinguishes between.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ByteClass {
+    Token,
+    CookieOctet,
+    Equals,
+    DoubleQuote,
+    Semicolon,
+    Space,
+    Tab,
+    Other,
+}
+
+const PAIR_START: &[ExpectedToken] = &[
+    ExpectedToken::CookieOctet,
+    ExpectedToken::Equals,
+    ExpectedToken::DoubleQuote,
+];
+
+/// The byte ranges of a cookie pair within the cookie string. The value range excludes the
+/// double quotes of a quoted value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Pair {
+    pub(crate) name: Range<usize>,
+    pub(crate) value: Range<usize>,
+    pub(crate) is_quoted: bool,
+}
+
+impl Pair {
+    /// Shifts the ranges of a pair parsed from a substring starting at `offset`.
+    pub(crate) fn offset_by(self, offset: usize) -> Pair {
+        Pair {
+            name: self.name.start + offset..self.name.end + offset,
+            value: self.value.start + offset..self.value.end + offset,
+            is_quoted: self.is_quoted,
+        }
+    }
+}
+
+/// A single-pass, non-allocating parser of the strict cookie string grammar, yielding the cookie
+/// pairs as they are completed.
+///
+/// Unlike the lalrpop parser, which reports errors per token, this walks the input byte by byte,
+/// so errors point at the offending character and list every token that could have followed.
+/// After an error, no more pairs are yielded.
+pub(crate) struct CookieParser<'a> {
+    input: &'a [u8],
+    non_ascii_policy: NonAsciiPolicy,
+    offset: usize,
+    state: State,
+    is_finished: bool,
+}
+
+impl<'a> CookieParser<'a> {
+    pub(crate) fn new(input: &'a str) -> CookieParser<'a> {
+        CookieParser::from_bytes(input.as_bytes(), NonAsciiPolicy::Reject)
+    }
+
+    /// Creates a parser of a cookie string given as bytes, which need not be valid UTF-8.
+    pub(crate) fn from_bytes(
+        input: &'a [u8],
+        non_ascii_policy: NonAsciiPolicy,
+    ) -> CookieParser<'a> {
+        CookieParser {
+            input,
+            non_ascii_policy,
+            offset: 0,
+            state: State::LeadingWhitespace,
+            is_finished: false,
+        }
+    }
+
+    fn fail(&mut self, kind: ParseErrorKind) -> Option<Result<Pair, ParseError>> {
+        self.is_finished = true;
+        Some(Err(ParseError::new(
+            kind,
+            self.input,
+            self.offset,
+            expected(self.state),
+        )))
+    }
+}
+
+impl<'a> Iterator for CookieParser<'a> {
+    type Item = Result<Pair, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+
+        let bytes = self.input;
+        let mut pair_start = self.offset;
+        let mut name_end = None;
+        let mut value_start = self.offset;
+        let mut value_end = None;
+
+        while self.offset < bytes.len() {
+            let class = classify(bytes[self.offset], self.non_ascii_policy);
+            let next_state = match next_state(self.state, class) {
+                Some(next_state) => next_state,
+                None => return self.fail(error_kind(self.state, class)),
+            };
+
+            match (self.state, next_state) {
+                (State::LeadingWhitespace, State::LeadingWhitespace) => (),
+                (State::LeadingWhitespace, _) | (State::PairStart, _) => {
+                    pair_start = self.offset;
+                    value_start = match next_state {
+                        State::Name { .. } => self.offset,
+                        _ => self.offset + 1,
+                    };
+                    if next_state == State::ValueStart {
+                        name_end = Some(self.offset);
+                    }
+                }
+                (State::Name { .. }, State::ValueStart) => {
+                    name_end = Some(self.offset);
+                    value_start = self.offset + 1;
+                }
+                (State::ValueStart, State::QuotedValue { .. }) => value_start = self.offset + 1,
+                (_, State::PairEnd) => value_end = Some(self.offset),
+                _ => (),
+            }
+
+            let completes_pair = is_in_pair(self.state) && !is_in_pair(next_state);
+            self.state = next_state;
+            self.offset += 1;
+
+            if completes_pair {
+                return Some(Ok(Pair {
+                    name: pair_start..name_end.unwrap_or(pair_start),
+                    value: value_start..value_end.unwrap_or(self.offset - 1),
+                    is_quoted: value_end.is_some(),
+                }));
+            }
+        }
+
+        if !accepts_end(self.state) {
+            return match self.state {
+                State::QuotedNamelessValue { .. } | State::QuotedValue { .. } => {
+                    self.fail(ParseErrorKind::UnterminatedQuote)
+                }
+                _ => self.fail(ParseErrorKind::UnexpectedEnd),
+            };
+        }
+
+        self.is_finished = true;
+        if is_in_pair(self.state) {
+            Some(Ok(Pair {
+                name: pair_start..name_end.unwrap_or(pair_start),
+                value: value_start..value_end.unwrap_or(self.offset),
+                is_quoted: value_end.is_some(),
+            }))
+        } else {
+            None
+        }
+    }
+}
+
+fn classify(byte: u8, non_ascii_policy: NonAsciiPolicy) -> ByteClass {
+    match byte {
+        0x80..=0xff if non_ascii_policy == NonAsciiPolicy::AllowInValues => ByteClass::CookieOctet,
+        b'=' => ByteClass::Equals,
+        b'"' => ByteClass::DoubleQuote,
+        b';' => ByteClass::Semicolon,
+        b' ' => ByteClass::Space,
+        b'\t' => ByteClass::Tab,
+        _ => match char_token_class(char::from(byte)) {
+            CharTokenClass::TokenOrCookieOctets => ByteClass::Token,
+            CharTokenClass::CookieOctets => ByteClass::CookieOctet,
+            CharTokenClass::None => ByteClass::Other,
+        },
+    }
+}
+
+fn next_state(state: State, class: ByteClass) -> Option<State> {
+    let is_whitespace = class == ByteClass::Space || class == ByteClass::Tab;
+
+    match state {
+        State::LeadingWhitespace if is_whitespace => Some(State::LeadingWhitespace),
+        State::LeadingWhitespace | State::PairStart => match class {
+            ByteClass::Token => Some(State::Name { is_token: true }),
+            ByteClass::CookieOctet => Some(State::Name { is_token: false }),
+            ByteClass::Equals => Some(State::ValueStart),
+            ByteClass::DoubleQuote => Some(State::QuotedNamelessValue { is_empty: true }),
+            _ => None,
+        },
+        State::Name { is_token } => match class {
+            ByteClass::Token => Some(State::Name { is_token }),
+            ByteClass::CookieOctet => Some(State::Name { is_token: false }),
+            ByteClass::Equals if is_token => Some(State::ValueStart),
+            ByteClass::Semicolon => Some(State::Semicolon),
+            _ if is_whitespace => Some(State::TrailingWhitespace),
+            _ => None,
+        },
+        State::QuotedNamelessValue { is_empty } => match class {
+            ByteClass::Token | ByteClass::CookieOctet => {
+                Some(State::QuotedNamelessValue { is_empty: false })
+            }
+            ByteClass::DoubleQuote if !is_empty => Some(State::PairEnd),
+            _ => None,
+        },
+        State::ValueStart | State::Value => match class {
+            ByteClass::Token | ByteClass::CookieOctet | ByteClass::Equals => Some(State::Value),
+            ByteClass::DoubleQuote if state == State::ValueStart => {
+                Some(State::QuotedValue { is_empty: true })
+            }
+            ByteClass::Semicolon => Some(State::Semicolon),
+            _ if is_whitespace => Some(State::TrailingWhitespace),
+            _ => None,
+        },
+        State::QuotedValue { is_empty } => match class {
+            ByteClass::Token | ByteClass::CookieOctet | ByteClass::Equals => {
+                Some(State::QuotedValue { is_empty: false })
+            }
+            ByteClass::DoubleQuote if !is_empty => Some(State::PairEnd),
+            _ => None,
+        },
+        State::PairEnd => match class {
+            ByteClass::Semicolon => Some(State::Semicolon),
+            _ if is_whitespace => Some(State::TrailingWhitespace),
+            _ => None,
+        },
+        State::Semicolon => match class {
+            ByteClass::Space => Some(State::PairStart),
+            _ => None,
+        },
+        State::TrailingWhitespace if is_whitespace => Some(State::TrailingWhitespace),
+        State::TrailingWhitespace => None,
+    }
+}
+
+fn is_in_pair(state: State) -> bool {
+    matches!(
+        state,
+        State::Name { .. }
+            | State::QuotedNamelessValue { .. }
+            | State::ValueStart
+            | State::Value
+            | State::QuotedValue { .. }
+            | State::PairEnd
+    )
+}
+
+fn accepts_end(state: State) -> bool {
+    matches!(
+        state,
+        State::Name { .. }
+            | State::ValueStart
+            | State::Value
+            | State::PairEnd
+            | State::TrailingWhitespace
+    )
+}
+
+fn expected(state: State) -> ExpectedTokens {
+    let pair_end = ExpectedTokens::of(&[
+        ExpectedToken::Semicolon,
+        ExpectedToken::Whitespace,
+        ExpectedToken::End,
+    ]);
+
+    match state {
+        State::LeadingWhitespace => ExpectedTokens::of(PAIR_START).with(ExpectedToken::Whitespace),
+        State::PairStart => ExpectedTokens::of(PAIR_START),
+        State::Name { is_token: true } => pair_end
+            .with(ExpectedToken::CookieOctet)
+            .with(ExpectedToken::Equals),
+        State::Name { is_token: false } => pair_end.with(ExpectedToken::CookieOctet),
+        State::QuotedNamelessValue { is_empty: true } => {
+            ExpectedTokens::of(&[ExpectedToken::CookieOctet])
+        }
+        State::QuotedNamelessValue { is_empty: false } => {
+            ExpectedTokens::of(&[ExpectedToken::CookieOctet, ExpectedToken::DoubleQuote])
+        }
+        State::ValueStart => pair_end
+            .with(ExpectedToken::CookieOctet)
+            .with(ExpectedToken::Equals)
+            .with(ExpectedToken::DoubleQuote),
+        State::Value => pair_end
+            .with(ExpectedToken::CookieOctet)
+            .with(ExpectedToken::Equals),
+        State::QuotedValue { is_empty: true } => {
+            ExpectedTokens::of(&[ExpectedToken::CookieOctet, ExpectedToken::Equals])
+        }
+        State::QuotedValue { is_empty: false } => ExpectedTokens::of(&[
+            ExpectedToken::CookieOctet,
+            ExpectedToken::Equals,
+            ExpectedToken::DoubleQuote,
+        ]),
+        State::PairEnd => pair_end,
+        State::Semicolon => ExpectedTokens::of(&[ExpectedToken::Space]),
+        State::TrailingWhitespace => {
+            ExpectedTokens::of(&[ExpectedToken::Whitespace, ExpectedToken::End])
+        }
+    }
+}
+
+fn error_kind(state: State, class: ByteClass) -> ParseErrorKind {
+    let starts_pair = matches!(
+        class,
+        ByteClass::Token | ByteClass::CookieOctet | ByteClass::Equals | ByteClass::DoubleQuote
+    );
+
+    match state {
+        State::Semicolon => ParseErrorKind::MissingSeparator,
+        State::PairEnd | State::TrailingWhitespace if starts_pair => {
+            ParseErrorKind::MissingSeparator
+        }
+        State::QuotedNamelessValue { .. } | State::QuotedValue { .. }
+            if class == ByteClass::Semicolon =>
+        {
+            ParseErrorKind::UnterminatedQuote
+        }
+        _ => ParseErrorKind::UnexpectedCharacter,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CookieParser, Pair};
+    use crate::{ExpectedToken, ParseError, ParseErrorKind};
+
+    /// Checks a cookie string against the strict grammar and locates the first error.
+    fn validate(input: &str) -> Result<(), ParseError> {
+        match CookieParser::new(input).find_map(Result::err) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    fn error(input: &str) -> (ParseErrorKind, usize, Option<char>, Vec<ExpectedToken>) {
+        let err = validate(input).unwrap_err();
+        (
+            err.kind(),
+            err.offset(),
+            err.character(),
+            err.expected().iter().collect(),
+        )
+    }
+
+    #[test]
+    fn valid() {
+        for input in &[
+            "a=1",
+            "a=1; b=2",
+            " \t a=1; b=\"2\" \t ",
+            "=",
+            "a=",
+            "=b",
+            "nokey",
+            "\"nokey\"",
+            "a=b=c",
+            "==",
+            "a=\"b=c\"",
+            "a(b",
+            "a=1; =; b=(2)",
+        ] {
+            assert_eq!(Ok(()), validate(input), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn pairs() {
+        assert_eq!(
+            vec![
+                Pair {
+                    name: 1..2,
+                    value: 3..4,
+                    is_quoted: false
+                },
+                Pair {
+                    name: 6..6,
+                    value: 8..9,
+                    is_quoted: true
+                },
+                Pair {
+                    name: 12..12,
+                    value: 13..14,
+                    is_quoted: true
+                },
+                Pair {
+                    name: 17..17,
+                    value: 17..19,
+                    is_quoted: false
+                },
+                Pair {
+                    name: 21..22,
+                    value: 23..23,
+                    is_quoted: false
+                }
+            ],
+            CookieParser::new(" a=1; =\"2\"; \"3\"; (4; b= \t")
+                .collect::<Result<Vec<Pair>, _>>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn unexpected_character() {
+        assert_eq!(
+            (
+                ParseErrorKind::UnexpectedCharacter,
+                3,
+                Some(','),
+                vec![
+                    ExpectedToken::CookieOctet,
+                    ExpectedToken::Equals,
+                    ExpectedToken::Semicolon,
+                    ExpectedToken::Whitespace,
+                    ExpectedToken::End
+                ]
+            ),
+            error("a=b,c")
+        );
+    }
+
+    #[test]
+    fn unexpected_non_ascii_character() {
+        assert_eq!(
+            (
+                ParseErrorKind::UnexpectedCharacter,
+                2,
+                Some('é'),
+                vec![
+                    ExpectedToken::CookieOctet,
+                    ExpectedToken::Equals,
+                    ExpectedToken::Semicolon,
+                    ExpectedToken::Whitespace,
+                    ExpectedToken::DoubleQuote,
+                    ExpectedToken::End
+                ]
+            ),
+            error("a=é")
+        );
+    }
+
+    #[test]
+    fn equals_after_non_token_name() {
+        assert_eq!(
+            (
+                ParseErrorKind::UnexpectedCharacter,
+                3,
+                Some('='),
+                vec![
+                    ExpectedToken::CookieOctet,
+                    ExpectedToken::Semicolon,
+                    ExpectedToken::Whitespace,
+                    ExpectedToken::End
+                ]
+            ),
+            error("a(b=c")
+        );
+    }
+
+    #[test]
+    fn empty_quoted_value() {
+        assert_eq!(
+            (
+                ParseErrorKind::UnexpectedCharacter,
+                3,
+                Some('"'),
+                vec![ExpectedToken::CookieOctet, ExpectedToken::Equals]
+            ),
+            error("a=\"\"")
+        );
+    }
+
+    #[test]
+    fn whitespace_in_quoted_value() {
+        assert_eq!(
+            (
+                ParseErrorKind::UnexpectedCharacter,
+                4,
+                Some(' '),
+                vec![
+                    ExpectedToken::CookieOctet,
+                    ExpectedToken::Equals,
+                    ExpectedToken::DoubleQuote
+                ]
+            ),
+            error("a=\"b c\"")
+        );
+    }
+
+    #[test]
+    fn unterminated_quote() {
+        assert_eq!(
+            (
+                ParseErrorKind::UnterminatedQuote,
+                4,
+                None,
+                vec![
+                    ExpectedToken::CookieOctet,
+                    ExpectedToken::Equals,
+                    ExpectedToken::DoubleQuote
+                ]
+            ),
+            error("a=\"b")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::UnterminatedQuote,
+                4,
+                Some(';'),
+                vec![ExpectedToken::CookieOctet, ExpectedToken::DoubleQuote]
+            ),
+            error("\"abc; d=e")
+        );
+    }
+
+    #[test]
+    fn missing_space_after_semicolon() {
+        assert_eq!(
+            (
+                ParseErrorKind::MissingSeparator,
+                4,
+                Some('b'),
+                vec![ExpectedToken::Space]
+            ),
+            error("a=1;b=2")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::MissingSeparator,
+                4,
+                Some('\t'),
+                vec![ExpectedToken::Space]
+            ),
+            error("a=1;\tb=2")
+        );
+    }
+
+    #[test]
+    fn missing_semicolon() {
+        assert_eq!(
+            (
+                ParseErrorKind::MissingSeparator,
+                4,
+                Some('b'),
+                vec![ExpectedToken::Whitespace, ExpectedToken::End]
+            ),
+            error("a=1 b=2")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::MissingSeparator,
+                5,
+                Some('b'),
+                vec![
+                    ExpectedToken::Semicolon,
+                    ExpectedToken::Whitespace,
+                    ExpectedToken::End
+                ]
+            ),
+            error("a=\"1\"b=2")
+        );
+    }
+
+    #[test]
+    fn whitespace_before_semicolon() {
+        assert_eq!(
+            (
+                ParseErrorKind::UnexpectedCharacter,
+                4,
+                Some(';'),
+                vec![ExpectedToken::Whitespace, ExpectedToken::End]
+            ),
+            error("a=1 ; b=2")
+        );
+    }
+
+    #[test]
+    fn extra_space_after_semicolon() {
+        assert_eq!(
+            (
+                ParseErrorKind::UnexpectedCharacter,
+                5,
+                Some(' '),
+                vec![
+                    ExpectedToken::CookieOctet,
+                    ExpectedToken::Equals,
+                    ExpectedToken::DoubleQuote
+                ]
+            ),
+            error("a=1;  b=2")
+        );
+    }
+
+    #[test]
+    fn unexpected_end() {
+        assert_eq!(
+            (
+                ParseErrorKind::UnexpectedEnd,
+                0,
+                None,
+                vec![
+                    ExpectedToken::CookieOctet,
+                    ExpectedToken::Equals,
+                    ExpectedToken::Whitespace,
+                    ExpectedToken::DoubleQuote
+                ]
+            ),
+            error("")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::UnexpectedEnd,
+                4,
+                None,
+                vec![ExpectedToken::Space]
+            ),
+            error("a=1;")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::UnexpectedEnd,
+                5,
+                None,
+                vec![
+                    ExpectedToken::CookieOctet,
+                    ExpectedToken::Equals,
+                    ExpectedToken::DoubleQuote
+                ]
+            ),
+            error("a=1; ")
+        );
+    }
+}
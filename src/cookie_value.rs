@@ -0,0 +1,236 @@
+use super::Error;
+use std::fmt::{Display, Error as FormatterError, Formatter};
+
+const VALUE_ERROR_DESCRIPTION: &str = "Value Error";
+
+/// A type that can be decoded from a cookie value, with `Cookie::parse_value`.
+///
+/// The value is unquoted before it is decoded.
+///
+/// # Examples
+///
+/// ```
+/// use basic_cookies::{Cookie, FromCookieValue};
+///
+/// struct Theme(bool);
+///
+/// impl FromCookieValue for Theme {
+///     type Err = std::str::ParseBoolError;
+///
+///     fn from_cookie_value(value: &str) -> Result<Theme, Self::Err> {
+///         value.parse().map(Theme)
+///     }
+/// }
+///
+/// let cookie = Cookie::parse("dark=true").unwrap().remove(0);
+/// assert!(cookie.parse_value::<Theme>().unwrap().0);
+/// ```
+pub trait FromCookieValue: Sized {
+    /// The error returned when the value cannot be decoded.
+    type Err: std::error::Error + Send + Sync + 'static;
+
+    /// Decodes a cookie value.
+    fn from_cookie_value(value: &str) -> Result<Self, Self::Err>;
+}
+
+/// Implements `FromCookieValue` with `str::parse`.
+macro_rules! impl_from_cookie_value_with_parse {
+    ($($ty:ty),*) => {
+        $(
+            impl FromCookieValue for $ty {
+                type Err = <$ty as std::str::FromStr>::Err;
+
+                fn from_cookie_value(value: &str) -> Result<$ty, Self::Err> {
+                    value.parse()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_cookie_value_with_parse!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, bool
+);
+
+impl FromCookieValue for String {
+    type Err = std::convert::Infallible;
+
+    fn from_cookie_value(value: &str) -> Result<String, Self::Err> {
+        Ok(value.to_owned())
+    }
+}
+
+/// A JSON cookie value, decoded with `serde_json`. Requires the `json` feature.
+///
+/// The strict grammar does not allow double quotes, commas or spaces in values, so JSON values
+/// other than numbers and literals can only be read in `ParseMode::Lenient`, unless the producer
+/// encodes them.
+///
+/// # Examples
+///
+/// ```
+/// use basic_cookies::{Cookie, Json, ParseMode};
+///
+/// let cookies = Cookie::parse_with_mode("prefs={\"volume\": 7}", ParseMode::Lenient).unwrap();
+/// let Json(prefs) = cookies[0].parse_value::<Json<serde_json::Value>>().unwrap();
+/// assert_eq!(7, prefs["volume"]);
+/// ```
+#[cfg(feature = "json")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Json<T>(pub T);
+
+#[cfg(feature = "json")]
+impl<T: serde::de::DeserializeOwned> FromCookieValue for Json<T> {
+    type Err = serde_json::Error;
+
+    fn from_cookie_value(value: &str) -> Result<Json<T>, Self::Err> {
+        serde_json::from_str(value).map(Json)
+    }
+}
+
+/// A cookie value that could not be decoded with `FromCookieValue`.
+#[derive(Debug)]
+pub struct ValueError {
+    name: String,
+    type_name: &'static str,
+    source: Box<dyn std::error::Error + Send + Sync>,
+}
+
+impl ValueError {
+    pub(crate) fn new<T: FromCookieValue>(name: &str, source: T::Err) -> ValueError {
+        ValueError {
+            name: name.to_owned(),
+            type_name: std::any::type_name::<T>(),
+            source: Box::new(source),
+        }
+    }
+
+    /// Gets the name of the cookie whose value could not be decoded.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the name of the type the value could not be decoded into, as given by
+    /// `std::any::type_name`.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    pub(crate) fn into_error(self) -> Error {
+        Error::ValueError(self)
+    }
+}
+
+impl Display for ValueError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.write_str(VALUE_ERROR_DESCRIPTION)?;
+        write!(
+            f,
+            ": value of cookie {:?} is not a valid {}: {}",
+            self.name, self.type_name, self.source
+        )
+    }
+}
+
+impl std::error::Error for ValueError {
+    fn description(&self) -> &str {
+        VALUE_ERROR_DESCRIPTION
+    }
+
+    fn cause(&self) -> Option<&dyn std::error::Error> {
+        self.source()
+    }
+
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FromCookieValue;
+    use crate::{Cookie, Error};
+    use std::error::Error as StdError;
+
+    fn parse_value<T: FromCookieValue>(value: &str) -> Result<T, Error> {
+        Cookie::new("name", value).parse_value::<T>()
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(-12, parse_value::<i32>("-12").unwrap());
+        assert_eq!(255, parse_value::<u8>("255").unwrap());
+        assert_eq!(
+            u128::MAX,
+            parse_value::<u128>(&u128::MAX.to_string()).unwrap()
+        );
+        assert_eq!(1.5, parse_value::<f64>("1.5").unwrap());
+
+        assert!(parse_value::<u8>("256").is_err());
+        assert!(parse_value::<u32>("-1").is_err());
+        assert!(parse_value::<i64>("").is_err());
+        assert!(parse_value::<i64>(" 1").is_err());
+    }
+
+    #[test]
+    fn bool() {
+        assert!(parse_value::<bool>("true").unwrap());
+        assert!(!parse_value::<bool>("false").unwrap());
+        assert!(parse_value::<bool>("1").is_err());
+        assert!(parse_value::<bool>("True").is_err());
+    }
+
+    #[test]
+    fn string() {
+        assert_eq!("", parse_value::<String>("").unwrap());
+
+        let cookies = Cookie::parse("a=\"quoted\"").unwrap();
+        assert_eq!("quoted", cookies[0].parse_value::<String>().unwrap());
+    }
+
+    #[test]
+    fn value_error() {
+        let cookies = Cookie::parse("a=1; count=lots").unwrap();
+        match cookies[1].parse_value::<u32>() {
+            Err(Error::ValueError(err)) => {
+                assert_eq!("count", err.name());
+                assert_eq!("u32", err.type_name());
+                assert_eq!(
+                    "invalid digit found in string",
+                    err.source().unwrap().to_string()
+                );
+                assert_eq!(
+                    "Cookie Parsing Error: Value Error: value of cookie \"count\" is not a \
+                     valid u32: invalid digit found in string",
+                    Error::ValueError(err).to_string()
+                );
+            }
+            res => panic!("expected value error, got {:?}", res),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json() {
+        use super::Json;
+        use crate::ParseMode;
+
+        assert_eq!(
+            Json(vec![1, 2]),
+            parse_value::<Json<Vec<u32>>>("[1,2]").unwrap()
+        );
+        assert_eq!(Json(7), parse_value::<Json<u8>>("7").unwrap());
+
+        let cookies = Cookie::parse_with_mode("a={\"b\": [true]}", ParseMode::Lenient).unwrap();
+        let Json(value) = cookies[0].parse_value::<Json<serde_json::Value>>().unwrap();
+        assert_eq!(true, value["b"][0]);
+
+        match parse_value::<Json<Vec<u32>>>("[1,") {
+            Err(Error::ValueError(err)) => {
+                assert_eq!("name", err.name());
+                assert!(err.type_name().contains("Json"));
+            }
+            res => panic!("expected value error, got {:?}", res),
+        }
+    }
+}
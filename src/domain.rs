@@ -0,0 +1,320 @@
+use super::punycode;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+const ACE_PREFIX: &str = "xn--";
+
+/// Converts a host name to the canonical form used for cookie domains, following
+/// [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-5.1.2).
+///
+/// Labels are lower-cased and labels with non-ASCII characters are converted to A-labels
+/// using Punycode. A single trailing dot is removed, so the fully qualified form of a host
+/// name canonicalizes the same as the relative form. IP addresses are lower-cased only.
+///
+/// Returns `None` if the host is empty or contains an empty label.
+///
+/// # Examples
+///
+/// ```
+/// use basic_cookies::canonicalize_host;
+///
+/// assert_eq!(Some("www.example.com".to_owned()), canonicalize_host("WWW.Example.COM."));
+/// assert_eq!(Some("xn--bcher-kva.example".to_owned()), canonicalize_host("Bücher.example"));
+/// assert_eq!(None, canonicalize_host("www..example.com"));
+/// ```
+pub fn canonicalize_host(host: &str) -> Option<String> {
+    let host = host.strip_suffix('.').unwrap_or(host);
+    if host.is_empty() {
+        return None;
+    }
+
+    if is_ip_address(host) {
+        return Some(host.to_ascii_lowercase());
+    }
+
+    let mut canonicalized = String::with_capacity(host.len());
+    for label in host.split('.') {
+        if label.is_empty() {
+            return None;
+        }
+
+        if !canonicalized.is_empty() {
+            canonicalized.push('.');
+        }
+
+        if label.is_ascii() {
+            canonicalized.extend(label.chars().map(|c| c.to_ascii_lowercase()));
+        } else {
+            let lowercase = label
+                .chars()
+                .flat_map(char::to_lowercase)
+                .collect::<Vec<char>>();
+            canonicalized.push_str(ACE_PREFIX);
+            punycode::encode(&lowercase, &mut canonicalized)?;
+        }
+    }
+
+    Some(canonicalized)
+}
+
+/// Determines whether a host domain-matches a cookie domain, following
+/// [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-5.1.3).
+///
+/// The host matches if it is identical to the domain, or if it is a host name, rather than an
+/// IP address, that ends with a `.` followed by the domain. The comparison ignores ASCII case,
+/// a leading dot on the domain, as allowed in the `Domain` attribute, and a trailing dot on
+/// either name. Non-ASCII names must be canonicalized with `canonicalize_host` first.
+///
+/// # Examples
+///
+/// ```
+/// use basic_cookies::domain_match;
+///
+/// assert!(domain_match("www.example.com", "example.com"));
+/// assert!(domain_match("www.example.com", ".Example.com"));
+/// assert!(!domain_match("www.notexample.com", "example.com"));
+/// assert!(!domain_match("example.com", "www.example.com"));
+/// assert!(!domain_match("192.168.0.1", "168.0.1"));
+/// ```
+pub fn domain_match(host: &str, domain: &str) -> bool {
+    let host = host.strip_suffix('.').unwrap_or(host);
+    let domain = domain.strip_prefix('.').unwrap_or(domain);
+    let domain = domain.strip_suffix('.').unwrap_or(domain);
+
+    if domain.is_empty() {
+        return false;
+    }
+
+    if host.eq_ignore_ascii_case(domain) {
+        return true;
+    }
+
+    host.len() > domain.len()
+        && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+        && host.as_bytes()[host.len() - domain.len()..].eq_ignore_ascii_case(domain.as_bytes())
+        && !is_ip_address(host)
+}
+
+pub(crate) fn is_ip_address(host: &str) -> bool {
+    match host.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+        Some(inner) => inner.parse::<Ipv6Addr>().is_ok(),
+        None => host.parse::<Ipv4Addr>().is_ok() || host.parse::<Ipv6Addr>().is_ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod canonicalize_host {
+        use super::super::canonicalize_host;
+
+        #[test]
+        fn lower_case() {
+            assert_eq!(
+                Some("example.com".to_owned()),
+                canonicalize_host("example.com")
+            );
+            assert_eq!(
+                Some("www.example.com".to_owned()),
+                canonicalize_host("WWW.ExAmPlE.CoM")
+            );
+        }
+
+        #[test]
+        fn trailing_dot() {
+            assert_eq!(
+                Some("example.com".to_owned()),
+                canonicalize_host("example.com.")
+            );
+            assert_eq!(None, canonicalize_host("example.com.."));
+            assert_eq!(None, canonicalize_host("."));
+        }
+
+        #[test]
+        fn empty_labels() {
+            assert_eq!(None, canonicalize_host(""));
+            assert_eq!(None, canonicalize_host(".example.com"));
+            assert_eq!(None, canonicalize_host("www..example.com"));
+        }
+
+        #[test]
+        fn single_label() {
+            assert_eq!(Some("localhost".to_owned()), canonicalize_host("LocalHost"));
+        }
+
+        #[test]
+        fn a_labels() {
+            assert_eq!(
+                Some("xn--bcher-kva.example".to_owned()),
+                canonicalize_host("bücher.example")
+            );
+            assert_eq!(
+                Some("xn--bcher-kva.example".to_owned()),
+                canonicalize_host("BÜCHER.Example")
+            );
+            assert_eq!(
+                Some("xn--wgv71a119e.jp".to_owned()),
+                canonicalize_host("日本語.jp")
+            );
+            assert_eq!(
+                Some("xn--fa-hia.de".to_owned()),
+                canonicalize_host("faß.de")
+            );
+            assert_eq!(Some("xn--tda.de".to_owned()), canonicalize_host("ü.de"));
+            assert_eq!(
+                Some("www.xn--zckzah.xn--r8jz45g".to_owned()),
+                canonicalize_host("www.テスト.例え")
+            );
+            assert_eq!(
+                Some("xn--80aealotwbjpid2k.xn--p1ai".to_owned()),
+                canonicalize_host("ПРАВИТЕЛЬСТВО.рф")
+            );
+        }
+
+        #[test]
+        fn existing_a_labels() {
+            assert_eq!(
+                Some("xn--bcher-kva.example".to_owned()),
+                canonicalize_host("XN--BCHER-KVA.example")
+            );
+        }
+
+        #[test]
+        fn ipv4() {
+            assert_eq!(
+                Some("192.168.0.1".to_owned()),
+                canonicalize_host("192.168.0.1")
+            );
+            assert_eq!(
+                Some("192.168.0.1".to_owned()),
+                canonicalize_host("192.168.0.1.")
+            );
+        }
+
+        #[test]
+        fn ipv6() {
+            assert_eq!(Some("[::1]".to_owned()), canonicalize_host("[::1]"));
+            assert_eq!(
+                Some("[2001:db8::ff00:42:8329]".to_owned()),
+                canonicalize_host("[2001:DB8::FF00:42:8329]")
+            );
+            assert_eq!(Some("::1".to_owned()), canonicalize_host("::1"));
+        }
+    }
+
+    mod domain_match {
+        use super::super::domain_match;
+
+        #[test]
+        fn identical() {
+            assert!(domain_match("example.com", "example.com"));
+            assert!(domain_match("localhost", "localhost"));
+            assert!(domain_match("com", "com"));
+        }
+
+        #[test]
+        fn identical_ignoring_case() {
+            assert!(domain_match("Example.COM", "example.com"));
+            assert!(domain_match("example.com", "EXAMPLE.com"));
+        }
+
+        #[test]
+        fn subdomain() {
+            assert!(domain_match("www.example.com", "example.com"));
+            assert!(domain_match("a.b.c.example.com", "example.com"));
+            assert!(domain_match("a.b.c.example.com", "c.example.com"));
+            assert!(domain_match("WWW.EXAMPLE.COM", "example.com"));
+            assert!(domain_match("www.example.com", "com"));
+        }
+
+        #[test]
+        fn superdomain() {
+            assert!(!domain_match("example.com", "www.example.com"));
+            assert!(!domain_match("com", "example.com"));
+        }
+
+        #[test]
+        fn suffix_without_dot() {
+            assert!(!domain_match("notexample.com", "example.com"));
+            assert!(!domain_match("wwwexample.com", "example.com"));
+            assert!(!domain_match("example.com", "ample.com"));
+        }
+
+        #[test]
+        fn unrelated() {
+            assert!(!domain_match("example.com", "example.org"));
+            assert!(!domain_match("example.com", "com.example"));
+            assert!(!domain_match("www.example.com", "www.example"));
+        }
+
+        #[test]
+        fn leading_dot() {
+            assert!(domain_match("example.com", ".example.com"));
+            assert!(domain_match("www.example.com", ".example.com"));
+            assert!(!domain_match("notexample.com", ".example.com"));
+            assert!(!domain_match("example.com", "..example.com"));
+        }
+
+        #[test]
+        fn trailing_dot() {
+            assert!(domain_match("example.com.", "example.com"));
+            assert!(domain_match("example.com", "example.com."));
+            assert!(domain_match("www.example.com.", "example.com."));
+            assert!(domain_match("www.example.com.", ".example.com."));
+            assert!(!domain_match("example.com..", "example.com"));
+        }
+
+        #[test]
+        fn empty() {
+            assert!(!domain_match("", ""));
+            assert!(!domain_match("example.com", ""));
+            assert!(!domain_match("example.com", "."));
+            assert!(!domain_match("", "example.com"));
+            assert!(!domain_match(".", "example.com"));
+        }
+
+        #[test]
+        fn ipv4_identical() {
+            assert!(domain_match("192.168.0.1", "192.168.0.1"));
+            assert!(domain_match("127.0.0.1", "127.0.0.1."));
+        }
+
+        #[test]
+        fn ipv4_never_suffix_matches() {
+            assert!(!domain_match("192.168.0.1", "168.0.1"));
+            assert!(!domain_match("192.168.0.1", "0.1"));
+            assert!(!domain_match("192.168.0.1", "1"));
+            assert!(!domain_match("192.168.0.1.", "168.0.1"));
+        }
+
+        #[test]
+        fn ipv4_lookalike_host_names_suffix_match() {
+            assert!(domain_match("1.2.3.4.example.com", "example.com"));
+            assert!(domain_match("256.168.0.1", "168.0.1"));
+        }
+
+        #[test]
+        fn ipv6_identical() {
+            assert!(domain_match("[::1]", "[::1]"));
+            assert!(domain_match("[2001:DB8::1]", "[2001:db8::1]"));
+        }
+
+        #[test]
+        fn ipv6_never_suffix_matches() {
+            assert!(!domain_match("[::1]", "1]"));
+            assert!(!domain_match("[2001:db8::1.2.3.4]", "2.3.4]"));
+            assert!(!domain_match("2001:db8::1.2.3.4", "2.3.4"));
+            assert!(!domain_match("::ffff:192.168.0.1", "168.0.1"));
+        }
+
+        #[test]
+        fn non_ascii() {
+            assert!(!domain_match("bücher.example", "her.example"));
+            assert!(!domain_match("xn--bcher-kva.example", "bücher.example"));
+        }
+
+        #[test]
+        fn different_lengths_same_suffix() {
+            assert!(!domain_match("a.example.com", "b.example.com"));
+            assert!(!domain_match("example.com", "xexample.com"));
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use super::cookie_parser::Pair;
+use super::token_class::trim_wsp;
+use super::{Cookie, LimitError, ParseLimits};
+use std::ops::Range;
+
+/// Parses a cookie string as described by `ParseMode::Lenient`. A value enclosed in double
+/// quotes is unquoted, as in the strict grammar.
+pub(crate) fn parse<'a>(
+    input: &'a str,
+    limits: &ParseLimits,
+) -> Result<Vec<Cookie<'a>>, LimitError> {
+    limits.check_input(input.len())?;
+
+    let mut cookies = Vec::new();
+    let mut segment_start = 0;
+    for segment in input.split(';') {
+        let pair = trim_wsp(input, segment_start..segment_start + segment.len());
+        segment_start += segment.len() + 1;
+
+        if pair.is_empty() {
+            continue;
+        }
+
+        let pair_start = pair.start;
+        let (name, value) = match input[pair.clone()].find('=') {
+            Some(idx) => (
+                trim_wsp(input, pair.start..pair.start + idx),
+                trim_wsp(input, pair.start + idx + 1..pair.end),
+            ),
+            None => (pair.start..pair.start, pair),
+        };
+
+        let cookie = Cookie::from_pair(input, unquote(input, name, value));
+        limits.check_cookie(
+            cookies.len(),
+            pair_start,
+            cookie.get_name().len(),
+            cookie.get_raw_value().len(),
+        )?;
+        cookies.push(cookie);
+    }
+
+    Ok(cookies)
+}
+
+fn unquote(input: &str, name: Range<usize>, value: Range<usize>) -> Pair {
+    let raw_value = &input[value.clone()];
+    if raw_value.len() >= 2 && raw_value.starts_with('"') && raw_value.ends_with('"') {
+        Pair {
+            name,
+            value: value.start + 1..value.end - 1,
+            is_quoted: true,
+        }
+    } else {
+        Pair {
+            name,
+            value,
+            is_quoted: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Cookie, ParseLimits};
+
+    fn parse(input: &str) -> Vec<Cookie<'_>> {
+        super::parse(input, &ParseLimits::unlimited()).unwrap()
+    }
+
+    fn pairs(input: &str) -> Vec<(&str, &str)> {
+        parse(input)
+            .iter()
+            .map(|cookie| (cookie.get_name(), cookie.get_value()))
+            .collect()
+    }
+
+    #[test]
+    fn strict_syntax() {
+        assert_eq!(vec![("a", "1"), ("b", "2")], pairs("a=1; b=2"));
+    }
+
+    #[test]
+    fn no_space_after_semicolon() {
+        assert_eq!(vec![("a", "1"), ("b", "2")], pairs("a=1;b=2"));
+    }
+
+    #[test]
+    fn extra_whitespace() {
+        assert_eq!(
+            vec![("a", "1"), ("b", "2")],
+            pairs(" \t a = 1 ;  \t b=2 \t")
+        );
+    }
+
+    #[test]
+    fn empty_pairs() {
+        assert_eq!(vec![("a", "1"), ("b", "2")], pairs("a=1;; ;b=2;"));
+        assert!(pairs("").is_empty());
+        assert!(pairs(" ; ;").is_empty());
+    }
+
+    #[test]
+    fn value_with_spaces_and_commas() {
+        assert_eq!(
+            vec![("a", "hello world"), ("b", "1,2,3")],
+            pairs("a=hello world; b=1,2,3")
+        );
+    }
+
+    #[test]
+    fn value_with_equals() {
+        assert_eq!(vec![("a", "b=c==")], pairs("a=b=c=="));
+    }
+
+    #[test]
+    fn quoted_value() {
+        assert_eq!(
+            vec![("a", "quoted value"), ("b", ""), ("c", "\"")],
+            pairs("a=\"quoted value\"; b=\"\"; c=\"")
+        );
+    }
+
+    #[test]
+    fn no_equals() {
+        assert_eq!(vec![("", "flag"), ("a", "1")], pairs("flag; a=1"));
+    }
+
+    #[test]
+    fn empty_name_or_value() {
+        assert_eq!(vec![("", "v"), ("n", ""), ("", "")], pairs("=v; n=; ="));
+    }
+
+    #[test]
+    fn non_ascii() {
+        assert_eq!(vec![("é", "ü€")], pairs("é=ü€"));
+    }
+
+    #[test]
+    fn spans() {
+        const COOKIE_STR: &str = " a = \"1\" ;flag;  b=";
+        let spans = parse(COOKIE_STR)
+            .iter()
+            .map(|cookie| {
+                (
+                    cookie.get_name_span(),
+                    cookie.get_value_span(),
+                    cookie.was_quoted(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                (1..2, 5..8, true),
+                (10..10, 10..14, false),
+                (17..18, 19..19, false)
+            ],
+            spans
+        );
+    }
+}
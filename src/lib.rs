@@ -1,9 +1,54 @@
+#[cfg(all(test, feature = "lalrpop"))]
 #[macro_use]
-pub(crate) extern crate lalrpop_util;
+extern crate lalrpop_util;
 
 mod cookie;
+mod cookie_date;
+mod cookie_header_writer;
+mod cookie_jar;
+#[cfg(all(test, feature = "lalrpop"))]
 mod cookie_lexer;
+mod cookie_map;
+mod cookie_parser;
+mod cookie_value;
+mod domain;
+mod lenient;
+#[cfg(all(test, feature = "lalrpop"))]
 mod linked_list;
+mod parse_error;
+mod parse_limits;
+mod path;
+#[cfg(feature = "psl")]
+mod public_suffix;
+mod punycode;
+mod recovery;
+#[cfg(all(test, feature = "lalrpop"))]
+mod reference_parser;
+mod request_url;
+mod set_cookie;
+mod set_cookie_builder;
+mod token_class;
 
-pub use cookie::{Cookie, Error};
+pub use cookie::{ByteCookie, Cookie, CookieBuf, Error, NonAsciiPolicy, ParseMode};
+pub use cookie_date::{format_cookie_date, parse_cookie_date};
+pub use cookie_header_writer::CookieHeaderWriter;
+pub use cookie_jar::{
+    CookieJar, CookieJarLimits, EvictedCookie, EvictionReason, RequestContext, StoreReport,
+    StoredCookie,
+};
+#[cfg(all(test, feature = "lalrpop"))]
 pub(crate) use cookie_lexer::{CookieLexer, CookieLexerError, CookieToken};
+pub use cookie_map::{CookieMap, DuplicateNameError, DuplicatePolicy, NameMatching};
+#[cfg(feature = "json")]
+pub use cookie_value::Json;
+pub use cookie_value::{FromCookieValue, ValueError};
+pub use domain::{canonicalize_host, domain_match};
+pub use parse_error::{ExpectedToken, ExpectedTokens, ParseError, ParseErrorKind};
+pub use parse_limits::{LimitError, LimitErrorKind, ParseLimits};
+pub use path::{default_path, path_match};
+#[cfg(feature = "psl")]
+pub use public_suffix::{is_public_suffix, registrable_domain};
+pub use recovery::{RecoveredCookies, SkipReason, SkippedSegment};
+pub use request_url::RequestUrl;
+pub use set_cookie::{SameSite, SetCookie, SetCookieAttribute};
+pub use set_cookie_builder::{BuildError, BuildErrorKind, SetCookieBuilder};
@@ -0,0 +1,558 @@
+use super::Error;
+use std::fmt::{Display, Error as FormatterError, Formatter, Write};
+use std::ops::Range;
+
+const PARSE_ERROR_DESCRIPTION: &str = "Parse Error";
+
+/// The reason a cookie string could not be parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ParseErrorKind {
+    /// A character that is not allowed at its position, such as a `,` or a space in a value.
+    UnexpectedCharacter,
+    /// A quoted value that is not closed before the next `;` or the end of the input.
+    UnterminatedQuote,
+    /// A cookie pair that is not followed by the `"; "` separator, such as the second pair in
+    /// `a=1;b=2` or in `a="1"b=2`.
+    MissingSeparator,
+    /// The input ended where a cookie pair or the rest of a separator was expected.
+    UnexpectedEnd,
+}
+
+/// A token the parser would have accepted at the position of a `ParseError`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExpectedToken {
+    /// A cookie-octet, which is any printable ASCII character except whitespace, `"`, `,`,
+    /// `;`, `\` and `=`.
+    CookieOctet,
+    /// The `=` between the name and the value.
+    Equals,
+    /// The `;` separating cookie pairs.
+    Semicolon,
+    /// The single space following a `;`.
+    Space,
+    /// Optional whitespace, which is any number of spaces and horizontal tabs.
+    Whitespace,
+    /// A `"` opening or closing a quoted value.
+    DoubleQuote,
+    /// The end of the input.
+    End,
+}
+
+const ALL_EXPECTED_TOKENS: [ExpectedToken; 7] = [
+    ExpectedToken::CookieOctet,
+    ExpectedToken::Equals,
+    ExpectedToken::Semicolon,
+    ExpectedToken::Space,
+    ExpectedToken::Whitespace,
+    ExpectedToken::DoubleQuote,
+    ExpectedToken::End,
+];
+
+/// The set of tokens the parser would have accepted at the position of a `ParseError`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ExpectedTokens(u8);
+
+/// A cookie string that does not follow the
+/// [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-4.2.1) syntax.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    offset: usize,
+    span_start: usize,
+    span_end: usize,
+    character: Option<char>,
+    expected: ExpectedTokens,
+    crumb: usize,
+}
+
+impl ExpectedToken {
+    fn as_str(self) -> &'static str {
+        match self {
+            ExpectedToken::CookieOctet => "cookie-octet",
+            ExpectedToken::Equals => "'='",
+            ExpectedToken::Semicolon => "';'",
+            ExpectedToken::Space => "' '",
+            ExpectedToken::Whitespace => "whitespace",
+            ExpectedToken::DoubleQuote => "'\"'",
+            ExpectedToken::End => "end of input",
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+impl Display for ExpectedToken {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ExpectedTokens {
+    pub(crate) fn of(tokens: &[ExpectedToken]) -> ExpectedTokens {
+        ExpectedTokens(tokens.iter().fold(0, |bits, token| bits | token.bit()))
+    }
+
+    pub(crate) fn with(self, token: ExpectedToken) -> ExpectedTokens {
+        ExpectedTokens(self.0 | token.bit())
+    }
+
+    /// Determines whether the set contains the token.
+    pub fn contains(&self, token: ExpectedToken) -> bool {
+        self.0 & token.bit() != 0
+    }
+
+    /// Determines whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Gets the number of tokens in the set.
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Iterates over the tokens in the set, in the order they are declared in `ExpectedToken`.
+    pub fn iter(&self) -> impl Iterator<Item = ExpectedToken> {
+        let tokens = *self;
+        ALL_EXPECTED_TOKENS
+            .iter()
+            .copied()
+            .filter(move |token| tokens.contains(*token))
+    }
+}
+
+impl std::fmt::Debug for ExpectedTokens {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl Display for ExpectedTokens {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        let len = self.len();
+        for (idx, token) in self.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(if idx + 1 == len { " or " } else { ", " })?;
+            }
+            token.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ParseError {
+    pub(crate) fn new(
+        kind: ParseErrorKind,
+        input: &[u8],
+        offset: usize,
+        expected: ExpectedTokens,
+    ) -> ParseError {
+        let (character, character_len) = match decode_char(&input[offset..]) {
+            Some((c, len)) => (Some(c), len),
+            None => (None, 0),
+        };
+        let (span_start, span_end) = match kind {
+            ParseErrorKind::UnexpectedCharacter => (offset, offset + character_len),
+            ParseErrorKind::UnterminatedQuote => {
+                let quote = input[..offset].iter().rposition(|byte| *byte == b'"');
+                (quote.unwrap_or(offset), offset)
+            }
+            ParseErrorKind::MissingSeparator => {
+                let rest = &input[offset..];
+                let len = rest
+                    .iter()
+                    .position(|byte| matches!(byte, b';' | b' ' | b'\t'))
+                    .unwrap_or(rest.len());
+                (offset, offset + len)
+            }
+            ParseErrorKind::UnexpectedEnd => (offset, offset),
+        };
+
+        ParseError {
+            kind,
+            offset,
+            span_start,
+            span_end,
+            character,
+            expected,
+            crumb: 0,
+        }
+    }
+
+    /// Gets the reason the input could not be parsed.
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// Gets the byte offset in the input at which parsing failed.
+    ///
+    /// This is the offset of the offending character, or of the end of the input if it ended
+    /// too early. Unterminated quotes are reported where the closing quote was missing.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Gets the byte range of the input the error refers to.
+    ///
+    /// This is the offending character, the whole pair that lacks a separator before it, or an
+    /// unterminated quoted value from its opening quote. It is empty if the input ended too early.
+    pub fn span(&self) -> Range<usize> {
+        self.span_start..self.span_end
+    }
+
+    /// Gets the character at the offset, or `None` if parsing failed at the end of the input.
+    ///
+    /// When parsing bytes, a byte that does not start a valid UTF-8 sequence is reported as
+    /// `char::REPLACEMENT_CHARACTER`.
+    pub fn character(&self) -> Option<char> {
+        self.character
+    }
+
+    /// Gets the tokens that would have been accepted at the offset.
+    pub fn expected(&self) -> ExpectedTokens {
+        self.expected
+    }
+
+    /// Gets the index of the header field the error is in, when parsing with
+    /// `Cookie::parse_many`. The offset and the span are relative to that header field.
+    ///
+    /// Errors in a single cookie string are always in header field 0.
+    pub fn crumb(&self) -> usize {
+        self.crumb
+    }
+
+    /// Renders the input with the span of the error underlined, followed by an explanation.
+    ///
+    /// The input must be the string that failed to parse. If the span of the error does not lie
+    /// within it on character boundaries, only the `Display` message is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::{Cookie, Error};
+    ///
+    /// let input = "session=38afes7a8;lang=en-US";
+    /// let err = match Cookie::parse(input) {
+    ///     Err(Error::ParseError(err)) => err,
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// assert_eq!(
+    ///     "session=38afes7a8;lang=en-US\n                  ^~~~~~~~~~\n\
+    ///      space required after ';' at byte 18, expected ' '",
+    ///     err.render(input)
+    /// );
+    /// ```
+    pub fn render(&self, input: &str) -> String {
+        let (before, span) = match (
+            input.get(..self.span_start),
+            input.get(self.span_start..self.span_end),
+        ) {
+            (Some(before), Some(span)) => (before, span),
+            _ => return self.to_string(),
+        };
+
+        let mut rendered = String::with_capacity(input.len() * 2 + 64);
+        rendered.push_str(input);
+        rendered.push('\n');
+
+        // Tabs are kept so that the marker lines up with the input when the tab is expanded.
+        for c in before.chars() {
+            rendered.push(if c == '\t' { '\t' } else { ' ' });
+        }
+
+        rendered.push('^');
+        let span_chars = span.chars().count();
+        for _ in 1..span_chars {
+            rendered.push('~');
+        }
+
+        rendered.push('\n');
+        let _ = self.write_explanation(&mut rendered);
+        rendered
+    }
+
+    pub(crate) fn offset_by(mut self, offset: usize) -> ParseError {
+        self.offset += offset;
+        self.span_start += offset;
+        self.span_end += offset;
+        self
+    }
+
+    pub(crate) fn in_crumb(mut self, crumb: usize) -> ParseError {
+        self.crumb = crumb;
+        self
+    }
+
+    fn write_explanation<W: Write>(&self, w: &mut W) -> Result<(), FormatterError> {
+        match (self.kind, self.character) {
+            (ParseErrorKind::MissingSeparator, _)
+                if self.expected.contains(ExpectedToken::Space) =>
+            {
+                w.write_str("space required after ';'")?
+            }
+            (ParseErrorKind::MissingSeparator, _) => w.write_str("missing \"; \" separator")?,
+            (ParseErrorKind::UnterminatedQuote, _) => w.write_str("unterminated quoted value")?,
+            (_, Some(c)) => write!(w, "unexpected character {:?}", c)?,
+            (_, None) => w.write_str("unexpected end of input")?,
+        }
+
+        write!(w, " at byte {}", self.offset)?;
+
+        if !self.expected.is_empty() {
+            write!(w, ", expected {}", self.expected)?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn into_error(self) -> Error {
+        Error::ParseError(self)
+    }
+}
+
+/// Decodes the UTF-8 character at the start of the bytes, returning it with its length. A byte
+/// that does not start a valid sequence decodes to the replacement character, with a length of 1.
+fn decode_char(bytes: &[u8]) -> Option<(char, usize)> {
+    let prefix = &bytes[..bytes.len().min(4)];
+    let valid = match std::str::from_utf8(prefix) {
+        Ok(valid) => valid,
+        Err(err) => std::str::from_utf8(&prefix[..err.valid_up_to()]).unwrap_or_default(),
+    };
+
+    match valid.chars().next() {
+        Some(c) => Some((c, c.len_utf8())),
+        None if bytes.is_empty() => None,
+        None => Some((char::REPLACEMENT_CHARACTER, 1)),
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.write_str(PARSE_ERROR_DESCRIPTION)?;
+        f.write_str(": ")?;
+        self.write_explanation(f)
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn description(&self) -> &str {
+        PARSE_ERROR_DESCRIPTION
+    }
+
+    fn cause(&self) -> Option<&dyn std::error::Error> {
+        None
+    }
+
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod expected_tokens {
+        use super::super::{ExpectedToken, ExpectedTokens};
+
+        #[test]
+        fn empty() {
+            let tokens = ExpectedTokens::default();
+            assert!(tokens.is_empty());
+            assert_eq!(0, tokens.len());
+            assert_eq!(None, tokens.iter().next());
+            assert_eq!("", tokens.to_string());
+        }
+
+        #[test]
+        fn contains() {
+            let tokens = ExpectedTokens::of(&[ExpectedToken::Space, ExpectedToken::End]);
+            assert!(tokens.contains(ExpectedToken::Space));
+            assert!(tokens.contains(ExpectedToken::End));
+            assert!(!tokens.contains(ExpectedToken::Semicolon));
+            assert_eq!(2, tokens.len());
+        }
+
+        #[test]
+        fn iter_in_declaration_order() {
+            let tokens = ExpectedTokens::of(&[ExpectedToken::End, ExpectedToken::CookieOctet])
+                .with(ExpectedToken::Equals);
+            assert_eq!(
+                vec![
+                    ExpectedToken::CookieOctet,
+                    ExpectedToken::Equals,
+                    ExpectedToken::End
+                ],
+                tokens.iter().collect::<Vec<ExpectedToken>>()
+            );
+        }
+
+        #[test]
+        fn display() {
+            assert_eq!(
+                "' '",
+                ExpectedTokens::of(&[ExpectedToken::Space]).to_string()
+            );
+            assert_eq!(
+                "';' or end of input",
+                ExpectedTokens::of(&[ExpectedToken::Semicolon, ExpectedToken::End]).to_string()
+            );
+            assert_eq!(
+                "cookie-octet, '=' or '\"'",
+                ExpectedTokens::of(&[
+                    ExpectedToken::CookieOctet,
+                    ExpectedToken::Equals,
+                    ExpectedToken::DoubleQuote
+                ])
+                .to_string()
+            );
+        }
+    }
+
+    mod parse_error {
+        use super::super::{ExpectedToken, ExpectedTokens, ParseError, ParseErrorKind};
+
+        #[test]
+        fn character() {
+            let expected = ExpectedTokens::of(&[ExpectedToken::CookieOctet]);
+            let err = ParseError::new(
+                ParseErrorKind::UnexpectedCharacter,
+                "a=é".as_bytes(),
+                2,
+                expected,
+            );
+            assert_eq!(Some('é'), err.character());
+
+            let err = ParseError::new(ParseErrorKind::UnexpectedEnd, b"a=1;", 4, expected);
+            assert_eq!(None, err.character());
+
+            let err = ParseError::new(ParseErrorKind::UnexpectedCharacter, b"a=\xe9", 2, expected);
+            assert_eq!(Some(char::REPLACEMENT_CHARACTER), err.character());
+            assert_eq!(2..3, err.span());
+        }
+
+        #[test]
+        fn display() {
+            let err = ParseError::new(
+                ParseErrorKind::UnexpectedCharacter,
+                b"a=b,c",
+                3,
+                ExpectedTokens::of(&[ExpectedToken::Semicolon, ExpectedToken::End]),
+            );
+            assert_eq!(
+                "Parse Error: unexpected character ',' at byte 3, expected ';' or end of input",
+                err.to_string()
+            );
+
+            let err = ParseError::new(
+                ParseErrorKind::MissingSeparator,
+                b"a=1;b=2",
+                4,
+                ExpectedTokens::of(&[ExpectedToken::Space]),
+            );
+            assert_eq!(
+                "Parse Error: space required after ';' at byte 4, expected ' '",
+                err.to_string()
+            );
+
+            let err = ParseError::new(
+                ParseErrorKind::UnexpectedEnd,
+                b"",
+                0,
+                ExpectedTokens::of(&[ExpectedToken::CookieOctet]),
+            );
+            assert_eq!(
+                "Parse Error: unexpected end of input at byte 0, expected cookie-octet",
+                err.to_string()
+            );
+        }
+
+        #[test]
+        fn span() {
+            let expected = ExpectedTokens::default();
+            let err = ParseError::new(
+                ParseErrorKind::UnexpectedCharacter,
+                "a=é".as_bytes(),
+                2,
+                expected,
+            );
+            assert_eq!(2..4, err.span());
+
+            let err = ParseError::new(ParseErrorKind::UnterminatedQuote, b"a=\"bc; d", 5, expected);
+            assert_eq!(2..5, err.span());
+
+            let err = ParseError::new(ParseErrorKind::MissingSeparator, b"a=1;bc=2 ", 4, expected);
+            assert_eq!(4..8, err.span());
+
+            let err = ParseError::new(ParseErrorKind::UnexpectedEnd, b"a=1;", 4, expected);
+            assert_eq!(4..4, err.span());
+
+            assert_eq!(14..14, err.offset_by(10).span());
+        }
+
+        fn render(kind: ParseErrorKind, input: &str, offset: usize) -> String {
+            ParseError::new(kind, input.as_bytes(), offset, ExpectedTokens::default()).render(input)
+        }
+
+        #[test]
+        fn render_unexpected_character() {
+            assert_eq!(
+                "a=b,c\n   ^\nunexpected character ',' at byte 3",
+                render(ParseErrorKind::UnexpectedCharacter, "a=b,c", 3)
+            );
+        }
+
+        #[test]
+        fn render_missing_separator() {
+            assert_eq!(
+                "a=1 bc=2\n    ^~~~\nmissing \"; \" separator at byte 4",
+                render(ParseErrorKind::MissingSeparator, "a=1 bc=2", 4)
+            );
+        }
+
+        #[test]
+        fn render_unterminated_quote() {
+            assert_eq!(
+                "a=\"bc; d=e\n  ^~~\nunterminated quoted value at byte 5",
+                render(ParseErrorKind::UnterminatedQuote, "a=\"bc; d=e", 5)
+            );
+        }
+
+        #[test]
+        fn render_unexpected_end() {
+            assert_eq!(
+                "a=1;\n    ^\nunexpected end of input at byte 4",
+                render(ParseErrorKind::UnexpectedEnd, "a=1;", 4)
+            );
+            assert_eq!(
+                "\n^\nunexpected end of input at byte 0",
+                render(ParseErrorKind::UnexpectedEnd, "", 0)
+            );
+        }
+
+        #[test]
+        fn render_counts_characters() {
+            assert_eq!(
+                "é=1;\tü=2,\n    \t   ^\nunexpected character ',' at byte 10",
+                render(ParseErrorKind::UnexpectedCharacter, "é=1;\tü=2,", 10)
+            );
+        }
+
+        #[test]
+        fn render_other_input() {
+            let err = ParseError::new(
+                ParseErrorKind::MissingSeparator,
+                b"a=1 bc=2",
+                4,
+                ExpectedTokens::default(),
+            );
+
+            assert_eq!(err.to_string(), err.render("short"));
+            assert_eq!(err.to_string(), err.render("a=1\u{e9}bc=2"));
+            assert_eq!(err.to_string(), err.render("a=1 bcd\u{e9}"));
+            assert_eq!(err.to_string(), err.render(""));
+        }
+    }
+}
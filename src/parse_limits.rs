@@ -0,0 +1,660 @@
+use super::cookie_parser::{CookieParser, Pair};
+use super::{
+    lenient, recovery, ByteCookie, Cookie, Error, NonAsciiPolicy, ParseError, ParseMode,
+    RecoveredCookies,
+};
+use std::fmt::{Display, Error as FormatterError, Formatter};
+
+const LIMIT_ERROR_DESCRIPTION: &str = "Limit Error";
+
+/// The maximum combined length of a cookie name and value, from
+/// [rfc6265bis](https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis#section-5.7).
+const RFC6265BIS_MAX_PAIR_LEN: usize = 4096;
+
+/// Limits on the size of cookie strings, to stop parsing hostile input early.
+///
+/// Every way of parsing a cookie string is available on the limits, and fails with an
+/// `Error::LimitError` as soon as a limit is exceeded. The parsing functions of `Cookie` have
+/// no limits. Lengths are counted in bytes, and the length of a quoted value includes its
+/// double quotes.
+///
+/// # Examples
+///
+/// ```
+/// use basic_cookies::{Error, LimitErrorKind, ParseLimits};
+///
+/// let limits = ParseLimits::unlimited().max_cookies(2);
+/// assert_eq!(2, limits.parse("a=1; b=2").unwrap().len());
+///
+/// match limits.parse("a=1; b=2; c=3") {
+///     Err(Error::LimitError(err)) => assert_eq!(LimitErrorKind::TooManyCookies, err.kind()),
+///     _ => unreachable!(),
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseLimits {
+    max_input_len: usize,
+    max_cookies: usize,
+    max_name_len: usize,
+    max_value_len: usize,
+    max_pair_len: usize,
+}
+
+impl ParseLimits {
+    /// Limits that accept cookie strings of any size. These are the default limits.
+    pub fn unlimited() -> ParseLimits {
+        ParseLimits {
+            max_input_len: usize::MAX,
+            max_cookies: usize::MAX,
+            max_name_len: usize::MAX,
+            max_value_len: usize::MAX,
+            max_pair_len: usize::MAX,
+        }
+    }
+
+    /// Limits suitable for parsing the `Cookie` header of a request: at most 8192 bytes, as
+    /// common HTTP servers allow for a header, and at most 180 cookies. Following
+    /// [rfc6265bis](https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis#section-5.7),
+    /// the name and the value of a cookie may be at most 4096 bytes long together.
+    pub fn recommended() -> ParseLimits {
+        ParseLimits::unlimited()
+            .max_input_len(8192)
+            .max_cookies(180)
+            .max_pair_len(RFC6265BIS_MAX_PAIR_LEN)
+    }
+
+    /// Sets the maximum length of the whole cookie string.
+    pub fn max_input_len(mut self, max_input_len: usize) -> ParseLimits {
+        self.max_input_len = max_input_len;
+        self
+    }
+
+    /// Sets the maximum number of cookies in the cookie string.
+    pub fn max_cookies(mut self, max_cookies: usize) -> ParseLimits {
+        self.max_cookies = max_cookies;
+        self
+    }
+
+    /// Sets the maximum length of a cookie name.
+    pub fn max_name_len(mut self, max_name_len: usize) -> ParseLimits {
+        self.max_name_len = max_name_len;
+        self
+    }
+
+    /// Sets the maximum length of a cookie value.
+    pub fn max_value_len(mut self, max_value_len: usize) -> ParseLimits {
+        self.max_value_len = max_value_len;
+        self
+    }
+
+    /// Sets the maximum combined length of the name and the value of a cookie.
+    pub fn max_pair_len(mut self, max_pair_len: usize) -> ParseLimits {
+        self.max_pair_len = max_pair_len;
+        self
+    }
+
+    /// Gets the maximum length of the whole cookie string.
+    pub fn get_max_input_len(&self) -> usize {
+        self.max_input_len
+    }
+
+    /// Gets the maximum number of cookies in the cookie string.
+    pub fn get_max_cookies(&self) -> usize {
+        self.max_cookies
+    }
+
+    /// Gets the maximum length of a cookie name.
+    pub fn get_max_name_len(&self) -> usize {
+        self.max_name_len
+    }
+
+    /// Gets the maximum length of a cookie value.
+    pub fn get_max_value_len(&self) -> usize {
+        self.max_value_len
+    }
+
+    /// Gets the maximum combined length of the name and the value of a cookie.
+    pub fn get_max_pair_len(&self) -> usize {
+        self.max_pair_len
+    }
+
+    /// Parses a cookie string like `Cookie::parse`, within the limits.
+    pub fn parse<'a>(&self, input: &'a str) -> Result<Vec<Cookie<'a>>, Error> {
+        self.iter(input).collect()
+    }
+
+    /// Parses a cookie string lazily like `Cookie::iter`, within the limits.
+    ///
+    /// A cookie string that is too long fails before any cookie is yielded. Cookies preceding
+    /// the first cookie that exceeds a limit are yielded before the error.
+    pub fn iter<'a>(&self, input: &'a str) -> impl Iterator<Item = Result<Cookie<'a>, Error>> {
+        LimitedPairs::new(CookieParser::new(input), *self, input.len())
+            .map(move |pair| pair.map(|pair| Cookie::from_pair(input, pair)))
+    }
+
+    /// Parses a cookie header split into several header fields like `Cookie::parse_many`, within
+    /// the limits.
+    ///
+    /// The limits apply to the header as a whole: the input length includes the `"; "` that
+    /// would join the header fields, and the cookies of every header field count towards the
+    /// maximum number of cookies.
+    pub fn parse_many<'a, I>(&self, crumbs: I) -> Result<Vec<Cookie<'a>>, Error>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let crumbs = crumbs.into_iter().collect::<Vec<&'a str>>();
+        let separators_len = crumbs.len().saturating_sub(1).saturating_mul(2);
+        let input_len = crumbs
+            .iter()
+            .fold(separators_len, |len, crumb| len.saturating_add(crumb.len()));
+        self.check_input(input_len)
+            .map_err(LimitError::into_error)?;
+
+        let mut cookies = Vec::new();
+        for (idx, crumb) in crumbs.into_iter().enumerate() {
+            for pair in CookieParser::new(crumb) {
+                let pair = pair.map_err(|err| err.in_crumb(idx).into_error())?;
+                self.check_pair(cookies.len(), &pair)
+                    .map_err(|err| err.in_crumb(idx).into_error())?;
+                cookies.push(Cookie::from_pair(crumb, pair).in_crumb(idx));
+            }
+        }
+
+        Ok(cookies)
+    }
+
+    /// Finds the first cookie with the given name like `Cookie::find`, within the limits.
+    pub fn find<'a>(&self, input: &'a str, name: &str) -> Result<Option<Cookie<'a>>, Error> {
+        let mut found = None;
+        for cookie in self.iter(input) {
+            let cookie = cookie?;
+            if found.is_none() && cookie.get_name() == name {
+                found = Some(cookie);
+            }
+        }
+
+        Ok(found)
+    }
+
+    /// Finds every cookie with the given name like `Cookie::find_all`, within the limits. All
+    /// cookies count towards the maximum number of cookies, not only the matching ones.
+    pub fn find_all<'a>(&self, input: &'a str, name: &str) -> Result<Vec<Cookie<'a>>, Error> {
+        self.iter(input)
+            .filter(|cookie| {
+                cookie
+                    .as_ref()
+                    .map_or(true, |cookie| cookie.get_name() == name)
+            })
+            .collect()
+    }
+
+    /// Parses a cookie string given as bytes like `Cookie::parse_bytes`, within the limits.
+    pub fn parse_bytes<'a>(&self, input: &'a [u8]) -> Result<Vec<ByteCookie<'a>>, Error> {
+        self.parse_bytes_with_policy(input, NonAsciiPolicy::Reject)
+    }
+
+    /// Parses a cookie string given as bytes like `Cookie::parse_bytes_with_policy`, within the
+    /// limits.
+    pub fn parse_bytes_with_policy<'a>(
+        &self,
+        input: &'a [u8],
+        non_ascii_policy: NonAsciiPolicy,
+    ) -> Result<Vec<ByteCookie<'a>>, Error> {
+        LimitedPairs::new(
+            CookieParser::from_bytes(input, non_ascii_policy),
+            *self,
+            input.len(),
+        )
+        .map(|pair| pair.map(|pair| ByteCookie::from_pair(input, pair)))
+        .collect()
+    }
+
+    /// Parses a cookie string using the given `ParseMode` like `Cookie::parse_with_mode`, within
+    /// the limits. Unlike other errors, exceeding a limit fails in `ParseMode::Lenient` too.
+    pub fn parse_with_mode<'a>(
+        &self,
+        input: &'a str,
+        mode: ParseMode,
+    ) -> Result<Vec<Cookie<'a>>, Error> {
+        match mode {
+            ParseMode::Strict => self.parse(input),
+            ParseMode::Lenient => lenient::parse(input, self).map_err(LimitError::into_error),
+        }
+    }
+
+    /// Parses a cookie string, skipping malformed cookie pairs, like `Cookie::parse_recovering`,
+    /// within the limits. Unlike malformed pairs, exceeding a limit fails the whole string.
+    pub fn parse_recovering<'a>(&self, input: &'a str) -> Result<RecoveredCookies<'a>, Error> {
+        recovery::parse_recovering(input, self).map_err(LimitError::into_error)
+    }
+
+    pub(crate) fn check_input(&self, input_len: usize) -> Result<(), LimitError> {
+        if input_len > self.max_input_len {
+            return Err(LimitError::new(
+                LimitErrorKind::InputTooLong,
+                self.max_input_len,
+                0,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Checks a pair of the strict grammar, counting the double quotes of a quoted value.
+    pub(crate) fn check_pair(&self, count: usize, pair: &Pair) -> Result<(), LimitError> {
+        let value_len = pair.value.len() + if pair.is_quoted { 2 } else { 0 };
+        self.check_cookie(count, pair.name.start, pair.name.len(), value_len)
+    }
+
+    /// Checks the cookie that follows `count` accepted cookies and starts at `offset`.
+    pub(crate) fn check_cookie(
+        &self,
+        count: usize,
+        offset: usize,
+        name_len: usize,
+        value_len: usize,
+    ) -> Result<(), LimitError> {
+        let (kind, limit) = if count >= self.max_cookies {
+            (LimitErrorKind::TooManyCookies, self.max_cookies)
+        } else if name_len > self.max_name_len {
+            (LimitErrorKind::NameTooLong, self.max_name_len)
+        } else if value_len > self.max_value_len {
+            (LimitErrorKind::ValueTooLong, self.max_value_len)
+        } else if name_len.saturating_add(value_len) > self.max_pair_len {
+            (LimitErrorKind::PairTooLong, self.max_pair_len)
+        } else {
+            return Ok(());
+        };
+
+        Err(LimitError::new(kind, limit, offset))
+    }
+}
+
+impl Default for ParseLimits {
+    fn default() -> ParseLimits {
+        ParseLimits::unlimited()
+    }
+}
+
+/// Applies parse limits to the pairs of a `CookieParser`, stopping at the first error.
+struct LimitedPairs<'a> {
+    pairs: CookieParser<'a>,
+    limits: ParseLimits,
+    count: usize,
+    input_error: Option<LimitError>,
+    is_finished: bool,
+}
+
+impl<'a> LimitedPairs<'a> {
+    fn new(pairs: CookieParser<'a>, limits: ParseLimits, input_len: usize) -> LimitedPairs<'a> {
+        LimitedPairs {
+            pairs,
+            limits,
+            count: 0,
+            input_error: limits.check_input(input_len).err(),
+            is_finished: false,
+        }
+    }
+
+    fn check(&mut self, pair: Result<Pair, ParseError>) -> Result<Pair, Error> {
+        let pair = pair.map_err(ParseError::into_error)?;
+
+        self.limits
+            .check_pair(self.count, &pair)
+            .map_err(LimitError::into_error)?;
+
+        self.count += 1;
+        Ok(pair)
+    }
+}
+
+impl<'a> Iterator for LimitedPairs<'a> {
+    type Item = Result<Pair, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+
+        if let Some(err) = self.input_error.take() {
+            self.is_finished = true;
+            return Some(Err(err.into_error()));
+        }
+
+        let pair = self.pairs.next()?;
+        let pair = self.check(pair);
+        self.is_finished = pair.is_err();
+        Some(pair)
+    }
+}
+
+/// The limit of `ParseLimits` a cookie string exceeded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LimitErrorKind {
+    /// The cookie string is longer than the maximum input length.
+    InputTooLong,
+    /// The cookie string has more cookies than the maximum number of cookies.
+    TooManyCookies,
+    /// A cookie name is longer than the maximum name length.
+    NameTooLong,
+    /// A cookie value is longer than the maximum value length.
+    ValueTooLong,
+    /// The name and the value of a cookie are longer together than the maximum pair length.
+    PairTooLong,
+}
+
+impl LimitErrorKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            LimitErrorKind::InputTooLong => "cookie string longer than",
+            LimitErrorKind::TooManyCookies => "number of cookies over",
+            LimitErrorKind::NameTooLong => "cookie name longer than",
+            LimitErrorKind::ValueTooLong => "cookie value longer than",
+            LimitErrorKind::PairTooLong => "cookie name and value longer than",
+        }
+    }
+}
+
+/// An error caused by a cookie string exceeding one of its `ParseLimits`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LimitError {
+    kind: LimitErrorKind,
+    limit: usize,
+    offset: usize,
+    crumb: usize,
+}
+
+impl LimitError {
+    fn new(kind: LimitErrorKind, limit: usize, offset: usize) -> LimitError {
+        LimitError {
+            kind,
+            limit,
+            offset,
+            crumb: 0,
+        }
+    }
+
+    /// Gets the limit that was exceeded.
+    pub fn kind(&self) -> LimitErrorKind {
+        self.kind
+    }
+
+    /// Gets the value of the limit that was exceeded.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Gets the byte offset of the cookie pair that exceeded the limit, or 0 if the whole cookie
+    /// string is too long.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Gets the index of the header field the offset is relative to, when parsing with
+    /// `ParseLimits::parse_many`. It is 0 for a single cookie string, or if all the header
+    /// fields together are too long.
+    pub fn crumb(&self) -> usize {
+        self.crumb
+    }
+
+    fn in_crumb(mut self, crumb: usize) -> LimitError {
+        self.crumb = crumb;
+        self
+    }
+
+    pub(crate) fn into_error(self) -> Error {
+        Error::LimitError(self)
+    }
+}
+
+impl Display for LimitError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.write_str(LIMIT_ERROR_DESCRIPTION)?;
+        write!(f, ": {} {}", self.kind.as_str(), self.limit)?;
+        match self.kind {
+            LimitErrorKind::InputTooLong => f.write_str(" bytes"),
+            LimitErrorKind::TooManyCookies => write!(f, " at byte {}", self.offset),
+            _ => write!(f, " bytes at byte {}", self.offset),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {
+    fn description(&self) -> &str {
+        LIMIT_ERROR_DESCRIPTION
+    }
+
+    fn cause(&self) -> Option<&dyn std::error::Error> {
+        None
+    }
+
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LimitErrorKind, ParseLimits};
+    use crate::{Error, NonAsciiPolicy, ParseMode};
+
+    fn limit_error<T: std::fmt::Debug>(result: Result<T, Error>) -> (LimitErrorKind, usize) {
+        match result {
+            Err(Error::LimitError(err)) => (err.kind(), err.offset()),
+            res => panic!("expected limit error, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn unlimited() {
+        let input = format!("a={}; b=2", "x".repeat(10_000));
+        assert_eq!(2, ParseLimits::unlimited().parse(&input).unwrap().len());
+        assert_eq!(ParseLimits::unlimited(), ParseLimits::default());
+    }
+
+    #[test]
+    fn recommended() {
+        let limits = ParseLimits::recommended();
+        assert_eq!(8192, limits.get_max_input_len());
+        assert_eq!(180, limits.get_max_cookies());
+        assert_eq!(4096, limits.get_max_pair_len());
+
+        let input = format!("a={}", "x".repeat(4095));
+        assert_eq!(1, limits.parse(&input).unwrap().len());
+
+        let input = format!("a={}", "x".repeat(4096));
+        assert_eq!(
+            (LimitErrorKind::PairTooLong, 0),
+            limit_error(limits.parse(&input))
+        );
+    }
+
+    #[test]
+    fn input_too_long() {
+        let limits = ParseLimits::unlimited().max_input_len(8);
+        assert_eq!(2, limits.parse("a=1; b=2").unwrap().len());
+
+        // The length is checked before the syntax.
+        assert_eq!(
+            (LimitErrorKind::InputTooLong, 0),
+            limit_error(limits.parse("a=1; b=2,"))
+        );
+
+        let mut cookies = limits.iter("a=1; b=23");
+        assert!(matches!(cookies.next(), Some(Err(Error::LimitError(_)))));
+        assert!(cookies.next().is_none());
+    }
+
+    #[test]
+    fn too_many_cookies() {
+        let limits = ParseLimits::unlimited().max_cookies(2);
+
+        let mut cookies = limits.iter("a=1; b=2; c=3; d=4");
+        assert_eq!("a", cookies.next().unwrap().unwrap().get_name());
+        assert_eq!("b", cookies.next().unwrap().unwrap().get_name());
+        assert_eq!(
+            (LimitErrorKind::TooManyCookies, 10),
+            limit_error(cookies.next().unwrap())
+        );
+        assert!(cookies.next().is_none());
+    }
+
+    #[test]
+    fn name_and_value_too_long() {
+        let limits = ParseLimits::unlimited()
+            .max_name_len(3)
+            .max_value_len(4)
+            .max_pair_len(6);
+
+        assert_eq!(2, limits.parse("abc=1; a=\"12\"").unwrap().len());
+        assert_eq!(
+            (LimitErrorKind::NameTooLong, 7),
+            limit_error(limits.parse("abc=1; abcd=1"))
+        );
+        assert_eq!(
+            (LimitErrorKind::ValueTooLong, 0),
+            limit_error(limits.parse("a=12345"))
+        );
+        assert_eq!(
+            (LimitErrorKind::ValueTooLong, 0),
+            limit_error(limits.parse("a=\"123\""))
+        );
+        assert_eq!(
+            (LimitErrorKind::PairTooLong, 0),
+            limit_error(limits.parse("abc=1234"))
+        );
+    }
+
+    #[test]
+    fn syntax_error_before_limit() {
+        let limits = ParseLimits::unlimited().max_cookies(1);
+        assert!(matches!(
+            limits.parse("a=,; b=2"),
+            Err(Error::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn find() {
+        let limits = ParseLimits::unlimited().max_cookies(2);
+        assert_eq!(
+            "1",
+            limits.find("a=1; b=2", "a").unwrap().unwrap().get_value()
+        );
+        assert_eq!(
+            (LimitErrorKind::TooManyCookies, 10),
+            limit_error(limits.find("a=1; b=2; c=3", "a"))
+        );
+        assert_eq!(
+            (LimitErrorKind::TooManyCookies, 10),
+            limit_error(limits.find_all("a=1; b=2; a=3", "a"))
+        );
+    }
+
+    #[test]
+    fn parse_bytes() {
+        let limits = ParseLimits::unlimited().max_value_len(3);
+        assert_eq!(1, limits.parse_bytes(b"a=123").unwrap().len());
+        assert_eq!(
+            (LimitErrorKind::ValueTooLong, 7),
+            limit_error(limits.parse_bytes_with_policy(
+                b"a=123; b=\xe9\xe9\xe9\xe9",
+                NonAsciiPolicy::AllowInValues
+            ))
+        );
+    }
+
+    #[test]
+    fn lenient() {
+        let limits = ParseLimits::unlimited().max_cookies(2).max_value_len(5);
+        assert_eq!(
+            2,
+            limits
+                .parse_with_mode("a=1;;b=hello", ParseMode::Lenient)
+                .unwrap()
+                .len()
+        );
+        assert_eq!(
+            (LimitErrorKind::TooManyCookies, 8),
+            limit_error(limits.parse_with_mode("a=1;b=2;c=3", ParseMode::Lenient))
+        );
+        assert_eq!(
+            (LimitErrorKind::ValueTooLong, 4),
+            limit_error(limits.parse_with_mode("a=1;b=hello world", ParseMode::Lenient))
+        );
+        assert_eq!(
+            (LimitErrorKind::InputTooLong, 0),
+            limit_error(
+                ParseLimits::unlimited()
+                    .max_input_len(2)
+                    .parse_with_mode("a=1", ParseMode::Lenient)
+            )
+        );
+    }
+
+    #[test]
+    fn recovering() {
+        let limits = ParseLimits::unlimited().max_cookies(2).max_name_len(1);
+
+        let recovered = limits.parse_recovering("a=1; b=,; c=3").unwrap();
+        assert_eq!(2, recovered.get_cookies().len());
+        assert_eq!(1, recovered.get_skipped().len());
+
+        assert_eq!(
+            (LimitErrorKind::TooManyCookies, 15),
+            limit_error(limits.parse_recovering("a=1; b=2; c=,; d=4"))
+        );
+        assert_eq!(
+            (LimitErrorKind::NameTooLong, 5),
+            limit_error(limits.parse_recovering("a=1; bc=2"))
+        );
+    }
+
+    #[test]
+    fn parse_many() {
+        let limits = ParseLimits::unlimited().max_input_len(13).max_cookies(3);
+        assert_eq!(3, limits.parse_many(vec!["a=1", "b=2; c=3"]).unwrap().len());
+
+        // The input length includes the separators joining the header fields.
+        assert_eq!(
+            (LimitErrorKind::InputTooLong, 0),
+            limit_error(limits.parse_many(vec!["a=1", "b=2", "c=34"]))
+        );
+
+        let limits = limits.max_input_len(100);
+        match limits.parse_many(vec!["a=1", "b=2", "c=3; d=4"]) {
+            Err(Error::LimitError(err)) => {
+                assert_eq!(LimitErrorKind::TooManyCookies, err.kind());
+                assert_eq!(2, err.crumb());
+                assert_eq!(5, err.offset());
+            }
+            res => panic!("expected limit error, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn display() {
+        let limits = ParseLimits::unlimited()
+            .max_input_len(8)
+            .max_cookies(1)
+            .max_value_len(2);
+
+        let err = limits.parse("a=1; b=234").unwrap_err();
+        assert_eq!(
+            "Cookie Parsing Error: Limit Error: cookie string longer than 8 bytes",
+            err.to_string()
+        );
+
+        let err = limits.parse("a=1; b=2").unwrap_err();
+        assert_eq!(
+            "Cookie Parsing Error: Limit Error: number of cookies over 1 at byte 5",
+            err.to_string()
+        );
+
+        let err = limits.parse("a=123").unwrap_err();
+        assert_eq!(
+            "Cookie Parsing Error: Limit Error: cookie value longer than 2 bytes at byte 0",
+            err.to_string()
+        );
+    }
+}
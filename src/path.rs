@@ -0,0 +1,169 @@
+/// Computes the default cookie path from the path of the request URI, following
+/// [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-5.1.4).
+///
+/// The default path is used for cookies without a valid `Path` attribute. The request path must
+/// not include the query or the fragment of the URI.
+///
+/// # Examples
+///
+/// ```
+/// use basic_cookies::default_path;
+///
+/// assert_eq!("/docs/api", default_path("/docs/api/index.html"));
+/// assert_eq!("/docs", default_path("/docs/"));
+/// assert_eq!("/", default_path("/index.html"));
+/// assert_eq!("/", default_path(""));
+/// ```
+pub fn default_path(request_path: &str) -> &str {
+    if !request_path.starts_with('/') {
+        return "/";
+    }
+
+    match request_path.rfind('/') {
+        Some(0) | None => "/",
+        Some(idx) => &request_path[..idx],
+    }
+}
+
+/// Determines whether a request path path-matches a cookie path, following
+/// [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-5.1.4).
+///
+/// The paths match if they are identical, or if the cookie path is a prefix of the request path
+/// and either ends with a `/` or is followed by a `/` in the request path. An empty request path
+/// is treated as `/`. The request path must not include the query or the fragment of the URI.
+///
+/// # Examples
+///
+/// ```
+/// use basic_cookies::path_match;
+///
+/// assert!(path_match("/foo", "/foo"));
+/// assert!(path_match("/foo/bar", "/foo"));
+/// assert!(path_match("/foo/bar", "/foo/"));
+/// assert!(!path_match("/foobar", "/foo"));
+/// assert!(!path_match("/foo", "/foo/"));
+/// ```
+pub fn path_match(request_path: &str, cookie_path: &str) -> bool {
+    let request_path = if request_path.is_empty() {
+        "/"
+    } else {
+        request_path
+    };
+
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path.as_bytes()[cookie_path.len()] == b'/'))
+}
+
+#[cfg(test)]
+mod tests {
+    mod default_path {
+        use super::super::default_path;
+
+        #[test]
+        fn empty() {
+            assert_eq!("/", default_path(""));
+        }
+
+        #[test]
+        fn relative() {
+            assert_eq!("/", default_path("foo"));
+            assert_eq!("/", default_path("foo/bar"));
+            assert_eq!("/", default_path("*"));
+        }
+
+        #[test]
+        fn root() {
+            assert_eq!("/", default_path("/"));
+        }
+
+        #[test]
+        fn single_segment() {
+            assert_eq!("/", default_path("/foo"));
+        }
+
+        #[test]
+        fn single_segment_trailing_slash() {
+            assert_eq!("/foo", default_path("/foo/"));
+        }
+
+        #[test]
+        fn multiple_segments() {
+            assert_eq!("/foo", default_path("/foo/bar"));
+            assert_eq!("/foo/bar", default_path("/foo/bar/baz.html"));
+        }
+
+        #[test]
+        fn multiple_segments_trailing_slash() {
+            assert_eq!("/foo/bar", default_path("/foo/bar/"));
+        }
+
+        #[test]
+        fn consecutive_slashes() {
+            assert_eq!("/", default_path("//"));
+            assert_eq!("/foo/", default_path("/foo//bar"));
+        }
+    }
+
+    mod path_match {
+        use super::super::path_match;
+
+        #[test]
+        fn identical() {
+            assert!(path_match("/", "/"));
+            assert!(path_match("/foo", "/foo"));
+            assert!(path_match("/foo/", "/foo/"));
+        }
+
+        #[test]
+        fn empty_request_path() {
+            assert!(path_match("", "/"));
+            assert!(!path_match("", "/foo"));
+        }
+
+        #[test]
+        fn root_cookie_path() {
+            assert!(path_match("/foo", "/"));
+            assert!(path_match("/foo/bar", "/"));
+        }
+
+        #[test]
+        fn prefix_followed_by_slash() {
+            assert!(path_match("/foo/", "/foo"));
+            assert!(path_match("/foo/bar", "/foo"));
+            assert!(path_match("/foo/bar/baz", "/foo/bar"));
+        }
+
+        #[test]
+        fn prefix_ending_with_slash() {
+            assert!(path_match("/foo/bar", "/foo/"));
+            assert!(path_match("/foo/bar/baz", "/foo/"));
+        }
+
+        #[test]
+        fn prefix_not_at_segment_boundary() {
+            assert!(!path_match("/foobar", "/foo"));
+            assert!(!path_match("/foo.html", "/foo"));
+            assert!(!path_match("/foo/barbaz", "/foo/bar"));
+        }
+
+        #[test]
+        fn cookie_path_longer() {
+            assert!(!path_match("/foo", "/foo/"));
+            assert!(!path_match("/foo", "/foo/bar"));
+            assert!(!path_match("/", "/foo"));
+        }
+
+        #[test]
+        fn case_sensitive() {
+            assert!(!path_match("/Foo", "/foo"));
+            assert!(!path_match("/foo/bar", "/FOO"));
+        }
+
+        #[test]
+        fn unrelated() {
+            assert!(!path_match("/bar", "/foo"));
+            assert!(!path_match("/bar/foo", "/foo"));
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use super::canonicalize_host;
+use super::domain::is_ip_address;
+
+const RULE_NORMAL: u8 = 1;
+const RULE_WILDCARD: u8 = 2;
+const RULE_EXCEPTION: u8 = 4;
+
+// Generated by the build script from the vendored copy of the Public Suffix List.
+include!(concat!(env!("OUT_DIR"), "/public_suffix_list.rs"));
+
+/// Determines whether a domain is a public suffix, such as `com` or `co.uk`, according to the
+/// bundled copy of the [Public Suffix List](https://publicsuffix.org/).
+///
+/// Domains that no rule applies to are treated as top-level domains, and therefore as public
+/// suffixes, as the list's algorithm requires. IP addresses are never public suffixes.
+///
+/// # Examples
+///
+/// ```
+/// use basic_cookies::is_public_suffix;
+///
+/// assert!(is_public_suffix("co.uk"));
+/// assert!(is_public_suffix("COM."));
+/// assert!(!is_public_suffix("example.co.uk"));
+/// ```
+pub fn is_public_suffix(domain: &str) -> bool {
+    match canonicalize_host(domain) {
+        Some(domain) => !is_ip_address(&domain) && public_suffix_start(&domain) == Some(0),
+        None => false,
+    }
+}
+
+/// Gets the registrable domain of a host, which is its public suffix together with the label
+/// preceding it, according to the bundled copy of the [Public Suffix List](https://publicsuffix.org/).
+///
+/// The result is canonicalized. Returns `None` for public suffixes, IP addresses and invalid
+/// host names.
+///
+/// # Examples
+///
+/// ```
+/// use basic_cookies::registrable_domain;
+///
+/// assert_eq!(Some("example.co.uk".to_owned()), registrable_domain("www.Example.co.uk"));
+/// assert_eq!(None, registrable_domain("co.uk"));
+/// ```
+pub fn registrable_domain(host: &str) -> Option<String> {
+    let host = canonicalize_host(host)?;
+    if is_ip_address(&host) {
+        return None;
+    }
+
+    let suffix_start = public_suffix_start(&host)?;
+    if suffix_start == 0 {
+        return None;
+    }
+
+    let domain_start = host[..suffix_start - 1].rfind('.').map_or(0, |idx| idx + 1);
+    Some(host[domain_start..].to_owned())
+}
+
+/// Finds the byte offset of the public suffix of a canonicalized host name.
+fn public_suffix_start(host: &str) -> Option<usize> {
+    let label_starts = std::iter::once(0)
+        .chain(host.match_indices('.').map(|(idx, _)| idx + 1))
+        .collect::<Vec<usize>>();
+
+    // An exception rule prevails over every other rule, and makes its parent the public suffix.
+    for (i, start) in label_starts.iter().enumerate() {
+        if rule_kinds(&host[*start..]) & RULE_EXCEPTION != 0 {
+            return label_starts.get(i + 1).copied();
+        }
+    }
+
+    // Otherwise the rule with the most labels prevails, defaulting to the top-level domain.
+    for (i, start) in label_starts.iter().enumerate() {
+        let candidate = &host[*start..];
+        if rule_kinds(candidate) & RULE_NORMAL != 0 {
+            return Some(*start);
+        }
+
+        if let Some(parent_start) = label_starts.get(i + 1) {
+            if rule_kinds(&host[*parent_start..]) & RULE_WILDCARD != 0 {
+                return Some(*start);
+            }
+        }
+    }
+
+    label_starts.last().copied()
+}
+
+fn rule_kinds(name: &str) -> u8 {
+    match RULES.binary_search_by(|(rule, _)| (*rule).cmp(name)) {
+        Ok(idx) => RULES[idx].1,
+        Err(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod is_public_suffix {
+        use super::super::is_public_suffix;
+
+        #[test]
+        fn top_level_domains() {
+            assert!(is_public_suffix("com"));
+            assert!(is_public_suffix("uk"));
+            assert!(is_public_suffix("jp"));
+        }
+
+        #[test]
+        fn unlisted_top_level_domain() {
+            assert!(is_public_suffix("example"));
+            assert!(is_public_suffix("localhost"));
+        }
+
+        #[test]
+        fn second_level_suffixes() {
+            assert!(is_public_suffix("co.uk"));
+            assert!(is_public_suffix("com.au"));
+            assert!(is_public_suffix("kyoto.jp"));
+        }
+
+        #[test]
+        fn private_suffixes() {
+            assert!(is_public_suffix("github.io"));
+            assert!(is_public_suffix("blogspot.com"));
+        }
+
+        #[test]
+        fn registrable_domains() {
+            assert!(!is_public_suffix("example.com"));
+            assert!(!is_public_suffix("example.co.uk"));
+            assert!(!is_public_suffix("www.example.com"));
+            assert!(!is_public_suffix("example.github.io"));
+        }
+
+        #[test]
+        fn wildcard() {
+            assert!(is_public_suffix("ck"));
+            assert!(is_public_suffix("anything.ck"));
+            assert!(!is_public_suffix("example.anything.ck"));
+            assert!(is_public_suffix("anything.kawasaki.jp"));
+            assert!(!is_public_suffix("kawasaki.jp"));
+        }
+
+        #[test]
+        fn exception() {
+            assert!(!is_public_suffix("www.ck"));
+            assert!(!is_public_suffix("city.kawasaki.jp"));
+            assert!(is_public_suffix("town.kawasaki.jp"));
+        }
+
+        #[test]
+        fn case_and_trailing_dot() {
+            assert!(is_public_suffix("CO.UK"));
+            assert!(is_public_suffix("co.uk."));
+            assert!(!is_public_suffix("Example.CO.UK."));
+        }
+
+        #[test]
+        fn idn() {
+            assert!(is_public_suffix("公司.cn"));
+            assert!(is_public_suffix("xn--55qx5d.cn"));
+            assert!(!is_public_suffix("example.公司.cn"));
+        }
+
+        #[test]
+        fn ip_addresses() {
+            assert!(!is_public_suffix("127.0.0.1"));
+            assert!(!is_public_suffix("[::1]"));
+        }
+
+        #[test]
+        fn invalid() {
+            assert!(!is_public_suffix(""));
+            assert!(!is_public_suffix("co..uk"));
+        }
+    }
+
+    mod registrable_domain {
+        use super::super::registrable_domain as registrable;
+
+        #[test]
+        fn simple() {
+            assert_eq!(Some("example.com".to_owned()), registrable("example.com"));
+            assert_eq!(
+                Some("example.com".to_owned()),
+                registrable("a.b.www.example.com")
+            );
+        }
+
+        #[test]
+        fn multi_label_suffix() {
+            assert_eq!(
+                Some("example.co.uk".to_owned()),
+                registrable("www.example.co.uk")
+            );
+            assert_eq!(
+                Some("example.github.io".to_owned()),
+                registrable("www.example.github.io")
+            );
+        }
+
+        #[test]
+        fn unlisted_top_level_domain() {
+            assert_eq!(
+                Some("example.test".to_owned()),
+                registrable("www.example.test")
+            );
+        }
+
+        #[test]
+        fn wildcard_and_exception() {
+            assert_eq!(
+                Some("example.anything.ck".to_owned()),
+                registrable("www.example.anything.ck")
+            );
+            assert_eq!(Some("www.ck".to_owned()), registrable("www.ck"));
+            assert_eq!(Some("www.ck".to_owned()), registrable("a.www.ck"));
+            assert_eq!(
+                Some("city.kawasaki.jp".to_owned()),
+                registrable("www.city.kawasaki.jp")
+            );
+        }
+
+        #[test]
+        fn canonicalized() {
+            assert_eq!(
+                Some("example.co.uk".to_owned()),
+                registrable("WWW.Example.Co.Uk.")
+            );
+            assert_eq!(
+                Some("xn--bcher-kva.xn--55qx5d.cn".to_owned()),
+                registrable("www.bücher.公司.cn")
+            );
+        }
+
+        #[test]
+        fn public_suffixes() {
+            assert_eq!(None, registrable("com"));
+            assert_eq!(None, registrable("co.uk"));
+            assert_eq!(None, registrable("anything.ck"));
+        }
+
+        #[test]
+        fn ip_addresses() {
+            assert_eq!(None, registrable("192.168.0.1"));
+            assert_eq!(None, registrable("[::1]"));
+        }
+    }
+}
@@ -0,0 +1,102 @@
+// [Punycode](https://tools.ietf.org/html/rfc3492) encoding of domain name labels.
+//
+// This module is also compiled into the build script, so it must not depend on the rest of
+// the crate.
+
+use std::convert::TryFrom;
+
+/// Encodes a label, without the ACE prefix.
+pub(crate) fn encode(input: &[char], output: &mut String) -> Option<()> {
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+    const INITIAL_BIAS: u32 = 72;
+    const INITIAL_N: u32 = 128;
+
+    let basic_count = input.iter().filter(|c| c.is_ascii()).count();
+    output.extend(input.iter().filter(|c| c.is_ascii()));
+    if basic_count > 0 {
+        output.push('-');
+    }
+
+    let mut n = INITIAL_N;
+    let mut delta = 0_u32;
+    let mut bias = INITIAL_BIAS;
+    let mut handled = basic_count;
+
+    while handled < input.len() {
+        let m = input
+            .iter()
+            .map(|c| u32::from(*c))
+            .filter(|c| *c >= n)
+            .min()?;
+        let handled_plus_one = u32::try_from(handled + 1).ok()?;
+        delta = delta.checked_add((m - n).checked_mul(handled_plus_one)?)?;
+        n = m;
+
+        for c in input.iter().map(|c| u32::from(*c)) {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+
+            if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = if k <= bias {
+                        T_MIN
+                    } else if k >= bias + T_MAX {
+                        T_MAX
+                    } else {
+                        k - bias
+                    };
+
+                    if q < t {
+                        break;
+                    }
+
+                    output.push(digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+
+                output.push(digit(q));
+                bias = adapt(delta, handled_plus_one, handled == basic_count);
+                delta = 0;
+                handled += 1;
+            }
+        }
+
+        delta = delta.checked_add(1)?;
+        n += 1;
+    }
+
+    Some(())
+}
+
+fn adapt(delta: u32, num_points: u32, first_time: bool) -> u32 {
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+    const SKEW: u32 = 38;
+    const DAMP: u32 = 700;
+
+    let mut delta = if first_time { delta / DAMP } else { delta / 2 };
+    delta += delta / num_points;
+
+    let mut k = 0;
+    while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+        delta /= BASE - T_MIN;
+        k += BASE;
+    }
+
+    k + (((BASE - T_MIN + 1) * delta) / (delta + SKEW))
+}
+
+fn digit(d: u32) -> char {
+    if d < 26 {
+        (b'a' + d as u8) as char
+    } else {
+        (b'0' + (d - 26) as u8) as char
+    }
+}
@@ -0,0 +1,223 @@
+use super::cookie_parser::CookieParser;
+use super::token_class::{is_wsp_char, trim_wsp};
+use super::{Cookie, LimitError, ParseError, ParseLimits};
+use std::ops::Range;
+
+/// The result of `Cookie::parse_recovering`: the well-formed cookies of a cookie string,
+/// together with the segments that were skipped.
+#[derive(Debug)]
+pub struct RecoveredCookies<'a> {
+    cookies: Vec<Cookie<'a>>,
+    skipped: Vec<SkippedSegment>,
+}
+
+/// A segment of a cookie string that `Cookie::parse_recovering` skipped.
+#[derive(Debug)]
+pub struct SkippedSegment {
+    start: usize,
+    end: usize,
+    reason: SkipReason,
+}
+
+/// The reason a segment of a cookie string was skipped.
+#[derive(Debug)]
+pub enum SkipReason {
+    /// The segment between two `;` separators, or after the last one, was empty.
+    Empty,
+    /// The segment is not a valid cookie pair. The offset of the error is relative to the
+    /// whole cookie string.
+    Malformed(ParseError),
+}
+
+impl<'a> RecoveredCookies<'a> {
+    /// Gets the well-formed cookies, in the order they appeared.
+    pub fn get_cookies(&self) -> &[Cookie<'a>] {
+        &self.cookies
+    }
+
+    /// Gets the skipped segments, in the order they appeared.
+    pub fn get_skipped(&self) -> &[SkippedSegment] {
+        &self.skipped
+    }
+
+    /// Determines whether every segment was a well-formed cookie.
+    pub fn is_complete(&self) -> bool {
+        self.skipped.is_empty()
+    }
+
+    /// Takes the well-formed cookies, discarding the skipped segments.
+    pub fn into_cookies(self) -> Vec<Cookie<'a>> {
+        self.cookies
+    }
+}
+
+impl SkippedSegment {
+    /// Gets the byte range of the segment in the cookie string, without surrounding whitespace.
+    /// Empty segments have an empty range at the position of the segment.
+    pub fn get_range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Gets the reason the segment was skipped.
+    pub fn get_reason(&self) -> &SkipReason {
+        &self.reason
+    }
+}
+
+pub(crate) fn parse_recovering<'a>(
+    input: &'a str,
+    limits: &ParseLimits,
+) -> Result<RecoveredCookies<'a>, LimitError> {
+    limits.check_input(input.len())?;
+
+    let mut recovered = RecoveredCookies {
+        cookies: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    if input.chars().all(is_wsp_char) {
+        return Ok(recovered);
+    }
+
+    let mut segment_start = 0;
+    for segment in input.split(';') {
+        let Range { start, end } = trim_wsp(input, segment_start..segment_start + segment.len());
+        let pair = &input[start..end];
+        segment_start += segment.len() + 1;
+
+        if pair.is_empty() {
+            recovered.skipped.push(SkippedSegment {
+                start,
+                end,
+                reason: SkipReason::Empty,
+            });
+            continue;
+        }
+
+        // The segment holds no `;`, so the parser yields at most one pair, which may be followed
+        // by an error, as in `a b=1`.
+        let mut pairs = CookieParser::new(pair);
+        match (pairs.next(), pairs.next()) {
+            (Some(Err(err)), _) | (_, Some(Err(err))) => recovered.skipped.push(SkippedSegment {
+                start,
+                end,
+                reason: SkipReason::Malformed(err.offset_by(start)),
+            }),
+            (Some(Ok(parsed)), _) => {
+                let parsed = parsed.offset_by(start);
+                limits.check_pair(recovered.cookies.len(), &parsed)?;
+                recovered.cookies.push(Cookie::from_pair(input, parsed));
+            }
+            (None, _) => {}
+        }
+    }
+
+    Ok(recovered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SkipReason;
+    use crate::{Cookie, ParseErrorKind};
+    use std::ops::Range;
+
+    fn cookies(input: &str) -> Vec<(&str, &str)> {
+        Cookie::parse_recovering(input)
+            .get_cookies()
+            .iter()
+            .map(|cookie| (cookie.get_name(), cookie.get_value()))
+            .collect()
+    }
+
+    fn skipped(input: &str) -> Vec<(Range<usize>, bool)> {
+        Cookie::parse_recovering(input)
+            .get_skipped()
+            .iter()
+            .map(|segment| {
+                (
+                    segment.get_range(),
+                    matches!(segment.get_reason(), SkipReason::Empty),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn well_formed() {
+        const COOKIE_STR: &str = "a=1; b=\"2\"; c=";
+        assert_eq!(vec![("a", "1"), ("b", "2"), ("c", "")], cookies(COOKIE_STR));
+        assert!(Cookie::parse_recovering(COOKIE_STR).is_complete());
+    }
+
+    #[test]
+    fn empty() {
+        assert!(cookies("").is_empty());
+        assert!(skipped("").is_empty());
+        assert!(skipped(" \t ").is_empty());
+    }
+
+    #[test]
+    fn malformed_pair() {
+        const COOKIE_STR: &str = "a=1; b=x,y; c=3";
+        assert_eq!(vec![("a", "1"), ("c", "3")], cookies(COOKIE_STR));
+        assert_eq!(vec![(5..10, false)], skipped(COOKIE_STR));
+        assert!(!Cookie::parse_recovering(COOKIE_STR).is_complete());
+    }
+
+    #[test]
+    fn malformed_first_and_last() {
+        const COOKIE_STR: &str = "a b=1; b=2; c=\"3";
+        assert_eq!(vec![("b", "2")], cookies(COOKIE_STR));
+        assert_eq!(vec![(0..5, false), (12..16, false)], skipped(COOKIE_STR));
+    }
+
+    #[test]
+    fn non_ascii() {
+        const COOKIE_STR: &str = "a=é; b=2";
+        assert_eq!(vec![("b", "2")], cookies(COOKIE_STR));
+        assert_eq!(vec![(0..4, false)], skipped(COOKIE_STR));
+    }
+
+    #[test]
+    fn empty_segments() {
+        const COOKIE_STR: &str = "a=1;; b=2; ";
+        assert_eq!(vec![("a", "1"), ("b", "2")], cookies(COOKIE_STR));
+        assert_eq!(vec![(4..4, true), (11..11, true)], skipped(COOKIE_STR));
+    }
+
+    #[test]
+    fn whitespace_around_separators() {
+        const COOKIE_STR: &str = " a=1 ;b=2;\t c=3 ";
+        assert_eq!(
+            vec![("a", "1"), ("b", "2"), ("c", "3")],
+            cookies(COOKIE_STR)
+        );
+        assert!(skipped(COOKIE_STR).is_empty());
+    }
+
+    #[test]
+    fn malformed_reason() {
+        let recovered = Cookie::parse_recovering("a=1; b=x,y");
+        match recovered.get_skipped()[0].get_reason() {
+            SkipReason::Malformed(err) => {
+                assert_eq!(ParseErrorKind::UnexpectedCharacter, err.kind());
+                assert_eq!(8, err.offset());
+                assert_eq!(Some(','), err.character());
+            }
+            SkipReason::Empty => panic!("expected a malformed segment"),
+        }
+    }
+
+    #[test]
+    fn spans() {
+        let recovered = Cookie::parse_recovering("x=,; a=1;  b=\"2\"");
+        let spans = recovered
+            .get_cookies()
+            .iter()
+            .map(|cookie| (cookie.get_name_span(), cookie.get_value_span()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec![(5..6, 7..8), (11..12, 13..16)], spans);
+        assert!(recovered.get_cookies()[1].was_quoted());
+    }
+}
@@ -0,0 +1,92 @@
+lalrpop_mod!(
+    #[allow(clippy::all, dead_code)]
+    cookie_grammar
+);
+
+use super::CookieLexer;
+
+/// Parses a cookie string with the lalrpop grammar, returning the name-value pairs in order,
+/// or `None` if the grammar rejects the input. The grammar is the reference implementation the
+/// hand-written parser is tested against.
+pub(crate) fn parse(input: &str) -> Option<Vec<(&str, &str)>> {
+    let cookies = cookie_grammar::CookiesParser::new()
+        .parse(CookieLexer::new(input))
+        .ok()?;
+
+    cookies
+        .clone_to_vec()
+        .iter()
+        .rev()
+        .map(|cookie| Some((cookie.key.as_str(input)?, cookie.value.as_str(input)?)))
+        .collect()
+}
+
+pub(crate) mod terminals {
+    use super::nonterminals::NonTerminalSpan;
+
+    #[derive(Clone, Debug)]
+    pub struct Cookie {
+        pub(crate) key: NonTerminalSpan,
+        pub(crate) value: NonTerminalSpan,
+    }
+}
+
+pub(crate) mod nonterminals {
+    #[derive(Clone, Debug)]
+    pub struct NonTerminalSpan {
+        start: usize,
+        end: usize,
+    }
+
+    impl NonTerminalSpan {
+        pub(crate) fn new(start: usize, end: usize) -> NonTerminalSpan {
+            NonTerminalSpan { start, end }
+        }
+
+        pub(crate) fn as_str<'a>(&self, data: &'a str) -> Option<&'a str> {
+            data.get(self.start..self.end)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::{Cookie, Error};
+
+    // The reference uses the current lexer, which rejects characters that are neither
+    // separators nor cookie octets. Before that change the lexer ended the input at such a
+    // character, so `a=b,c` parsed as `a=b`. This test therefore does not show equivalence with
+    // that earlier behavior.
+    #[test]
+    fn hand_written_parser_agrees_with_grammar() {
+        const ALPHABET: &[char] = &['a', '(', '=', '"', ';', ' ', '\t', ',', 'é'];
+        const MAX_LEN: u32 = 5;
+
+        for len in 0..=MAX_LEN {
+            for mut idx in 0..ALPHABET.len().pow(len) {
+                let mut input = String::new();
+                for _ in 0..len {
+                    input.push(ALPHABET[idx % ALPHABET.len()]);
+                    idx /= ALPHABET.len();
+                }
+
+                let parsed_cookies = Cookie::parse(&input).map(|cookies| {
+                    cookies
+                        .iter()
+                        .map(|cookie| (cookie.get_name(), cookie.get_value()))
+                        .collect::<Vec<(&str, &str)>>()
+                });
+
+                match (parse(&input), parsed_cookies) {
+                    (Some(expected), Ok(actual)) => assert_eq!(expected, actual, "{:?}", input),
+                    (None, Err(Error::ParseError(_))) => {}
+                    (expected, actual) => panic!(
+                        "{:?}: grammar returned {:?}, parser returned {:?}",
+                        input, expected, actual
+                    ),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+/// The parts of an absolute request URL that cookie storage and retrieval depend on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequestUrl<'a> {
+    scheme: &'a str,
+    host: &'a str,
+    path: &'a str,
+}
+
+impl<'a> RequestUrl<'a> {
+    /// Parses an absolute URL of the form `scheme://[userinfo@]host[:port][/path][?query][#fragment]`.
+    ///
+    /// Returns `None` if the URL has no scheme or no host.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::RequestUrl;
+    ///
+    /// let url = RequestUrl::parse("https://user@www.example.com:8443/docs/index.html?q=1").unwrap();
+    ///
+    /// assert_eq!("https", url.get_scheme());
+    /// assert_eq!("www.example.com", url.get_host());
+    /// assert_eq!("/docs/index.html", url.get_path());
+    /// assert!(url.is_secure());
+    /// ```
+    pub fn parse(input: &'a str) -> Option<RequestUrl<'a>> {
+        let scheme_end = input.find("://")?;
+        let scheme = &input[..scheme_end];
+        if scheme.is_empty()
+            || !scheme
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"+-.".contains(&b))
+        {
+            return None;
+        }
+
+        let rest = &input[scheme_end + 3..];
+        let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+        let authority = &rest[..authority_end];
+        let host_and_port = match authority.rfind('@') {
+            Some(idx) => &authority[idx + 1..],
+            None => authority,
+        };
+
+        let host = if host_and_port.starts_with('[') {
+            &host_and_port[..host_and_port.find(']')? + 1]
+        } else {
+            match host_and_port.find(':') {
+                Some(idx) => &host_and_port[..idx],
+                None => host_and_port,
+            }
+        };
+
+        if host.is_empty() {
+            return None;
+        }
+
+        let path_and_query = &rest[authority_end..];
+        let path_end = path_and_query
+            .find(['?', '#'])
+            .unwrap_or(path_and_query.len());
+
+        Some(RequestUrl {
+            scheme,
+            host,
+            path: &path_and_query[..path_end],
+        })
+    }
+
+    /// Gets the scheme of the URL, such as `https`.
+    pub fn get_scheme(&self) -> &'a str {
+        self.scheme
+    }
+
+    /// Gets the host of the URL, without the port. IPv6 addresses keep their brackets.
+    pub fn get_host(&self) -> &'a str {
+        self.host
+    }
+
+    /// Gets the path of the URL, without the query and the fragment. It may be empty.
+    pub fn get_path(&self) -> &'a str {
+        self.path
+    }
+
+    /// Determines whether the scheme denotes a secure protocol, which is `https` or `wss`.
+    pub fn is_secure(&self) -> bool {
+        self.scheme.eq_ignore_ascii_case("https") || self.scheme.eq_ignore_ascii_case("wss")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RequestUrl;
+
+    #[test]
+    fn simple() {
+        let url = RequestUrl::parse("http://example.com/a/b").unwrap();
+        assert_eq!("http", url.get_scheme());
+        assert_eq!("example.com", url.get_host());
+        assert_eq!("/a/b", url.get_path());
+        assert!(!url.is_secure());
+    }
+
+    #[test]
+    fn empty_path() {
+        let url = RequestUrl::parse("https://example.com").unwrap();
+        assert_eq!("example.com", url.get_host());
+        assert_eq!("", url.get_path());
+
+        let url = RequestUrl::parse("https://example.com?q=/x").unwrap();
+        assert_eq!("example.com", url.get_host());
+        assert_eq!("", url.get_path());
+    }
+
+    #[test]
+    fn query_and_fragment() {
+        let url = RequestUrl::parse("https://example.com/a?b=/c#d/e").unwrap();
+        assert_eq!("/a", url.get_path());
+
+        let url = RequestUrl::parse("https://example.com/a#d?e").unwrap();
+        assert_eq!("/a", url.get_path());
+    }
+
+    #[test]
+    fn userinfo_and_port() {
+        let url = RequestUrl::parse("wss://user:p@ss@example.com:8080/").unwrap();
+        assert_eq!("example.com", url.get_host());
+        assert_eq!("/", url.get_path());
+        assert!(url.is_secure());
+    }
+
+    #[test]
+    fn ipv6() {
+        let url = RequestUrl::parse("http://[::1]:8080/x").unwrap();
+        assert_eq!("[::1]", url.get_host());
+        assert_eq!("/x", url.get_path());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(RequestUrl::parse("example.com/a").is_none());
+        assert!(RequestUrl::parse("://example.com/a").is_none());
+        assert!(RequestUrl::parse("http:///a").is_none());
+        assert!(RequestUrl::parse("http://user@:80/a").is_none());
+        assert!(RequestUrl::parse("http://[::1/a").is_none());
+        assert!(RequestUrl::parse("ht tp://example.com/").is_none());
+    }
+}
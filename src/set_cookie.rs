@@ -0,0 +1,390 @@
+use super::parse_cookie_date;
+use std::time::SystemTime;
+
+/// A cookie received in a `Set-Cookie` response header.
+#[derive(Debug)]
+pub struct SetCookie<'a> {
+    name: &'a str,
+    value: &'a str,
+    attributes: Vec<SetCookieAttribute<'a>>,
+}
+
+/// A recognised cookie attribute of a `Set-Cookie` header.
+///
+/// Unrecognised attributes, and recognised attributes with values the user agent
+/// is required to ignore, are not represented.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SetCookieAttribute<'a> {
+    /// The expiry time given by the `Expires` attribute. The attribute is ignored if its value
+    /// is not a valid cookie date.
+    Expires(SystemTime),
+    /// The number of seconds until the cookie expires. Zero or negative values mean the cookie
+    /// has already expired.
+    MaxAge(i64),
+    /// The value of the `Domain` attribute, without its leading `.`, if any.
+    /// The domain is not lower-cased.
+    Domain(&'a str),
+    /// The value of the `Path` attribute, or `None` if the value was empty or did not start with
+    /// a `/`, in which case the default path of the request URI applies.
+    Path(Option<&'a str>),
+    Secure,
+    HttpOnly,
+    SameSite(SameSite),
+    Partitioned,
+}
+
+/// The value of the `SameSite` cookie attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+impl<'a> SetCookie<'a> {
+    /// Parses a `Set-Cookie` header value using the
+    /// [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-5.2) user agent algorithm.
+    ///
+    /// Returns `None` if the algorithm requires the header to be ignored entirely, which is the
+    /// case when the name-value pair lacks a `=` character or the cookie name is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::{SetCookie, SetCookieAttribute};
+    ///
+    /// let set_cookie = SetCookie::parse("SID=31d4d96e407aad42; Path=/; Secure").unwrap();
+    ///
+    /// assert_eq!("SID", set_cookie.get_name());
+    /// assert_eq!("31d4d96e407aad42", set_cookie.get_value());
+    /// assert_eq!(
+    ///     &[SetCookieAttribute::Path(Some("/")), SetCookieAttribute::Secure],
+    ///     set_cookie.get_attributes()
+    /// );
+    /// ```
+    pub fn parse(input: &'a str) -> Option<SetCookie<'a>> {
+        let (name_value_pair, unparsed_attributes) = match input.find(';') {
+            Some(idx) => (&input[..idx], &input[idx..]),
+            None => (input, ""),
+        };
+
+        let (name, value) = split_at_equals(name_value_pair)?;
+        if name.is_empty() {
+            return None;
+        }
+
+        Some(SetCookie {
+            name,
+            value,
+            attributes: unparsed_attributes
+                .split(';')
+                .skip(1)
+                .filter_map(parse_attribute)
+                .collect(),
+        })
+    }
+
+    /// Gets the name of the cookie.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::SetCookie;
+    ///
+    /// let set_cookie = SetCookie::parse("name=value; HttpOnly").unwrap();
+    /// assert_eq!("name", set_cookie.get_name());
+    /// ```
+    pub fn get_name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Gets the value of the cookie.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::SetCookie;
+    ///
+    /// let set_cookie = SetCookie::parse("name=value; HttpOnly").unwrap();
+    /// assert_eq!("value", set_cookie.get_value());
+    /// ```
+    pub fn get_value(&self) -> &'a str {
+        self.value
+    }
+
+    /// Gets the recognised attributes of the cookie, in the order they appeared in the header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::{SetCookie, SetCookieAttribute};
+    ///
+    /// let set_cookie = SetCookie::parse("name=value; Max-Age=60; HttpOnly").unwrap();
+    /// assert_eq!(
+    ///     &[SetCookieAttribute::MaxAge(60), SetCookieAttribute::HttpOnly],
+    ///     set_cookie.get_attributes()
+    /// );
+    /// ```
+    pub fn get_attributes(&self) -> &[SetCookieAttribute<'a>] {
+        &self.attributes
+    }
+}
+
+fn split_at_equals(pair: &str) -> Option<(&str, &str)> {
+    let idx = pair.find('=')?;
+    Some((trim_wsp(&pair[..idx]), trim_wsp(&pair[idx + 1..])))
+}
+
+fn trim_wsp(s: &str) -> &str {
+    s.trim_matches(|c| c == '\x20' || c == '\x09')
+}
+
+fn parse_attribute(cookie_av: &str) -> Option<SetCookieAttribute<'_>> {
+    let (name, value) = split_at_equals(cookie_av).unwrap_or((trim_wsp(cookie_av), ""));
+
+    if name.eq_ignore_ascii_case("Expires") {
+        parse_cookie_date(value).map(SetCookieAttribute::Expires)
+    } else if name.eq_ignore_ascii_case("Max-Age") {
+        parse_max_age(value).map(SetCookieAttribute::MaxAge)
+    } else if name.eq_ignore_ascii_case("Domain") {
+        match value.strip_prefix('.').unwrap_or(value) {
+            "" => None,
+            domain => Some(SetCookieAttribute::Domain(domain)),
+        }
+    } else if name.eq_ignore_ascii_case("Path") {
+        Some(SetCookieAttribute::Path(if value.starts_with('/') {
+            Some(value)
+        } else {
+            None
+        }))
+    } else if name.eq_ignore_ascii_case("Secure") {
+        Some(SetCookieAttribute::Secure)
+    } else if name.eq_ignore_ascii_case("HttpOnly") {
+        Some(SetCookieAttribute::HttpOnly)
+    } else if name.eq_ignore_ascii_case("SameSite") {
+        parse_same_site(value).map(SetCookieAttribute::SameSite)
+    } else if name.eq_ignore_ascii_case("Partitioned") {
+        Some(SetCookieAttribute::Partitioned)
+    } else {
+        None
+    }
+}
+
+fn parse_max_age(value: &str) -> Option<i64> {
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value),
+    };
+
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    // Values too large to represent are clamped rather than ignored.
+    let magnitude = digits.bytes().fold(0_i64, |acc, b| {
+        acc.saturating_mul(10).saturating_add(i64::from(b - b'0'))
+    });
+
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+fn parse_same_site(value: &str) -> Option<SameSite> {
+    if value.eq_ignore_ascii_case("Strict") {
+        Some(SameSite::Strict)
+    } else if value.eq_ignore_ascii_case("Lax") {
+        Some(SameSite::Lax)
+    } else if value.eq_ignore_ascii_case("None") {
+        Some(SameSite::None)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SameSite, SetCookie, SetCookieAttribute};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn attributes(input: &str) -> Vec<SetCookieAttribute<'_>> {
+        SetCookie::parse(input).unwrap().get_attributes().to_vec()
+    }
+
+    #[test]
+    fn name_and_value() {
+        const SET_COOKIE_STR: &str = "test=1234";
+        let set_cookie = SetCookie::parse(SET_COOKIE_STR).unwrap();
+        assert_eq!("test", set_cookie.get_name());
+        assert_eq!("1234", set_cookie.get_value());
+        assert!(set_cookie.get_attributes().is_empty());
+    }
+
+    #[test]
+    fn name_and_value_trimmed() {
+        const SET_COOKIE_STR: &str = " \x09test \x09= 12 34\x09; Secure";
+        let set_cookie = SetCookie::parse(SET_COOKIE_STR).unwrap();
+        assert_eq!("test", set_cookie.get_name());
+        assert_eq!("12 34", set_cookie.get_value());
+    }
+
+    #[test]
+    fn value_with_equals_and_quotes() {
+        const SET_COOKIE_STR: &str = "test=\"abc=123\"";
+        let set_cookie = SetCookie::parse(SET_COOKIE_STR).unwrap();
+        assert_eq!("test", set_cookie.get_name());
+        assert_eq!("\"abc=123\"", set_cookie.get_value());
+    }
+
+    #[test]
+    fn empty_value() {
+        const SET_COOKIE_STR: &str = "test=; Path=/";
+        let set_cookie = SetCookie::parse(SET_COOKIE_STR).unwrap();
+        assert_eq!("test", set_cookie.get_name());
+        assert_eq!("", set_cookie.get_value());
+    }
+
+    #[test]
+    fn ignored_without_equals() {
+        assert!(SetCookie::parse("test; Path=/").is_none());
+    }
+
+    #[test]
+    fn ignored_with_empty_name() {
+        assert!(SetCookie::parse("=test").is_none());
+        assert!(SetCookie::parse(" \x09 =test; Secure").is_none());
+    }
+
+    #[test]
+    fn ignored_when_empty() {
+        assert!(SetCookie::parse("").is_none());
+    }
+
+    #[test]
+    fn attributes_case_insensitive() {
+        const SET_COOKIE_STR: &str =
+            "a=b; EXPIRES=Wed, 21 Oct 2015 07:28:00 GMT; max-age=10; dOmAiN=example.com; \
+             pATH=/x; SECURE; httponly; samesite=lax; partitioned";
+        let set_cookie = SetCookie::parse(SET_COOKIE_STR).unwrap();
+        assert_eq!(
+            &[
+                SetCookieAttribute::Expires(UNIX_EPOCH + Duration::from_secs(1_445_412_480)),
+                SetCookieAttribute::MaxAge(10),
+                SetCookieAttribute::Domain("example.com"),
+                SetCookieAttribute::Path(Some("/x")),
+                SetCookieAttribute::Secure,
+                SetCookieAttribute::HttpOnly,
+                SetCookieAttribute::SameSite(SameSite::Lax),
+                SetCookieAttribute::Partitioned,
+            ],
+            set_cookie.get_attributes()
+        );
+    }
+
+    #[test]
+    fn attributes_trimmed() {
+        const SET_COOKIE_STR: &str = "a=b;Path = /x \x09;  Secure ;HttpOnly";
+        let set_cookie = SetCookie::parse(SET_COOKIE_STR).unwrap();
+        assert_eq!(
+            &[
+                SetCookieAttribute::Path(Some("/x")),
+                SetCookieAttribute::Secure,
+                SetCookieAttribute::HttpOnly,
+            ],
+            set_cookie.get_attributes()
+        );
+    }
+
+    #[test]
+    fn unknown_and_empty_attributes_ignored() {
+        const SET_COOKIE_STR: &str = "a=b; ; Foo=bar; Comment; Secure;";
+        let set_cookie = SetCookie::parse(SET_COOKIE_STR).unwrap();
+        assert_eq!(&[SetCookieAttribute::Secure], set_cookie.get_attributes());
+    }
+
+    #[test]
+    fn flag_attributes_with_values() {
+        const SET_COOKIE_STR: &str = "a=b; Secure=false; HttpOnly=0";
+        let set_cookie = SetCookie::parse(SET_COOKIE_STR).unwrap();
+        assert_eq!(
+            &[SetCookieAttribute::Secure, SetCookieAttribute::HttpOnly],
+            set_cookie.get_attributes()
+        );
+    }
+
+    #[test]
+    fn max_age() {
+        assert_eq!(
+            vec![SetCookieAttribute::MaxAge(3600)],
+            attributes("a=b; Max-Age=3600")
+        );
+        assert_eq!(
+            vec![SetCookieAttribute::MaxAge(-1)],
+            attributes("a=b; Max-Age=-1")
+        );
+        assert_eq!(
+            vec![SetCookieAttribute::MaxAge(0)],
+            attributes("a=b; Max-Age=0")
+        );
+        assert_eq!(
+            vec![SetCookieAttribute::MaxAge(i64::MAX)],
+            attributes("a=b; Max-Age=99999999999999999999999")
+        );
+        assert!(attributes("a=b; Max-Age=").is_empty());
+        assert!(attributes("a=b; Max-Age=-").is_empty());
+        assert!(attributes("a=b; Max-Age=+1").is_empty());
+        assert!(attributes("a=b; Max-Age=1a").is_empty());
+        assert!(attributes("a=b; Max-Age=1.5").is_empty());
+    }
+
+    #[test]
+    fn expires() {
+        assert_eq!(
+            vec![SetCookieAttribute::Expires(
+                UNIX_EPOCH + Duration::from_secs(784_111_777)
+            )],
+            attributes("a=b; Expires=Sunday, 06-Nov-94 08:49:37 GMT")
+        );
+        assert!(attributes("a=b; Expires=").is_empty());
+        assert!(attributes("a=b; Expires=tomorrow").is_empty());
+        assert!(attributes("a=b; Expires=Sun, 06 Nov 1600 08:49:37 GMT").is_empty());
+    }
+
+    #[test]
+    fn domain() {
+        assert_eq!(
+            vec![SetCookieAttribute::Domain("Example.com")],
+            attributes("a=b; Domain=.Example.com")
+        );
+        assert!(attributes("a=b; Domain=").is_empty());
+        assert!(attributes("a=b; Domain=.").is_empty());
+    }
+
+    #[test]
+    fn path() {
+        assert_eq!(
+            vec![SetCookieAttribute::Path(Some("/"))],
+            attributes("a=b; Path=/")
+        );
+        assert_eq!(
+            vec![SetCookieAttribute::Path(None)],
+            attributes("a=b; Path=")
+        );
+        assert_eq!(
+            vec![SetCookieAttribute::Path(None)],
+            attributes("a=b; Path=foo/bar")
+        );
+    }
+
+    #[test]
+    fn same_site() {
+        assert_eq!(
+            vec![SetCookieAttribute::SameSite(SameSite::Strict)],
+            attributes("a=b; SameSite=STRICT")
+        );
+        assert_eq!(
+            vec![SetCookieAttribute::SameSite(SameSite::None)],
+            attributes("a=b; SameSite=none")
+        );
+        assert!(attributes("a=b; SameSite=sometimes").is_empty());
+        assert!(attributes("a=b; SameSite").is_empty());
+    }
+}
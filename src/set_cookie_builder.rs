@@ -0,0 +1,560 @@
+use super::token_class::{is_cookie_name, is_cookie_value};
+use super::{format_cookie_date, Error, SameSite};
+use std::fmt::{Display, Error as FormatterError, Formatter};
+use std::time::{Duration, SystemTime};
+
+const BUILD_ERROR_DESCRIPTION: &str = "Build Error";
+
+const SECURE_PREFIX: &str = "__Secure-";
+const HOST_PREFIX: &str = "__Host-";
+
+/// Builds a serialized `Set-Cookie` header value.
+///
+/// # Examples
+///
+/// ```
+/// use basic_cookies::{SameSite, SetCookieBuilder};
+/// use std::time::Duration;
+///
+/// let header = SetCookieBuilder::new("SID", "31d4d96e407aad42")
+///     .path("/")
+///     .max_age(Duration::from_secs(3600))
+///     .secure(true)
+///     .http_only(true)
+///     .same_site(SameSite::Lax)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(
+///     "SID=31d4d96e407aad42; Max-Age=3600; Path=/; Secure; HttpOnly; SameSite=Lax",
+///     header
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct SetCookieBuilder<'a> {
+    name: &'a str,
+    value: &'a str,
+    expires: Option<SystemTime>,
+    max_age: Option<Duration>,
+    domain: Option<&'a str>,
+    path: Option<&'a str>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<SameSite>,
+    partitioned: bool,
+}
+
+impl<'a> SetCookieBuilder<'a> {
+    /// Creates a builder for a cookie with the given name and value and no attributes.
+    ///
+    /// The value may be enclosed in double quotes, which are kept as part of the value.
+    pub fn new(name: &'a str, value: &'a str) -> SetCookieBuilder<'a> {
+        SetCookieBuilder {
+            name,
+            value,
+            expires: None,
+            max_age: None,
+            domain: None,
+            path: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+            partitioned: false,
+        }
+    }
+
+    /// Sets the `Expires` attribute.
+    pub fn expires(mut self, expires: SystemTime) -> SetCookieBuilder<'a> {
+        self.expires = Some(expires);
+        self
+    }
+
+    /// Sets the `Max-Age` attribute. Sub-second precision is discarded.
+    pub fn max_age(mut self, max_age: Duration) -> SetCookieBuilder<'a> {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Sets the `Domain` attribute.
+    ///
+    /// With the `psl` feature enabled, public suffixes such as `co.uk` are rejected when building.
+    pub fn domain(mut self, domain: &'a str) -> SetCookieBuilder<'a> {
+        self.domain = Some(domain);
+        self
+    }
+
+    /// Sets the `Path` attribute. The path must start with a `/`.
+    pub fn path(mut self, path: &'a str) -> SetCookieBuilder<'a> {
+        self.path = Some(path);
+        self
+    }
+
+    /// Sets whether the `Secure` attribute is present.
+    pub fn secure(mut self, secure: bool) -> SetCookieBuilder<'a> {
+        self.secure = secure;
+        self
+    }
+
+    /// Sets whether the `HttpOnly` attribute is present.
+    pub fn http_only(mut self, http_only: bool) -> SetCookieBuilder<'a> {
+        self.http_only = http_only;
+        self
+    }
+
+    /// Sets the `SameSite` attribute. `SameSite::None` requires the `Secure` attribute.
+    pub fn same_site(mut self, same_site: SameSite) -> SetCookieBuilder<'a> {
+        self.same_site = Some(same_site);
+        self
+    }
+
+    /// Sets whether the `Partitioned` attribute is present. It requires the `Secure` attribute.
+    pub fn partitioned(mut self, partitioned: bool) -> SetCookieBuilder<'a> {
+        self.partitioned = partitioned;
+        self
+    }
+
+    /// Validates the cookie and serializes it as a `Set-Cookie` header value.
+    ///
+    /// Fails if the name is not a token, the value contains characters other than cookie
+    /// octets, an attribute value is malformed, or the attributes contradict each other or the
+    /// `__Secure-` and `__Host-` name prefixes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basic_cookies::{BuildErrorKind, Error, SetCookieBuilder};
+    ///
+    /// match SetCookieBuilder::new("name", "white space").build() {
+    ///     Err(Error::BuildError(err)) => assert_eq!(BuildErrorKind::InvalidValue, err.kind()),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn build(&self) -> Result<String, Error> {
+        self.validate().map_err(BuildError::into_error)?;
+
+        let expires = match self.expires {
+            Some(expires) => Some(
+                format_cookie_date(expires)
+                    .ok_or_else(|| BuildError::new(BuildErrorKind::InvalidExpires).into_error())?,
+            ),
+            None => None,
+        };
+
+        Ok(self.serialize(expires.as_deref()))
+    }
+
+    fn validate(&self) -> Result<(), BuildError> {
+        if !is_valid_name(self.name) {
+            return Err(BuildError::new(BuildErrorKind::InvalidName));
+        }
+
+        if !is_cookie_value(self.value) {
+            return Err(BuildError::new(BuildErrorKind::InvalidValue));
+        }
+
+        if let Some(domain) = self.domain {
+            if !is_valid_domain(domain) {
+                return Err(BuildError::new(BuildErrorKind::InvalidDomain));
+            }
+
+            #[cfg(feature = "psl")]
+            {
+                if super::is_public_suffix(domain) {
+                    return Err(BuildError::new(BuildErrorKind::InvalidDomain));
+                }
+            }
+        }
+
+        if let Some(path) = self.path {
+            if !is_valid_path(path) {
+                return Err(BuildError::new(BuildErrorKind::InvalidPath));
+            }
+        }
+
+        if !self.secure
+            && (self.same_site == Some(SameSite::None)
+                || self.partitioned
+                || self.name.starts_with(SECURE_PREFIX)
+                || self.name.starts_with(HOST_PREFIX))
+        {
+            return Err(BuildError::new(BuildErrorKind::SecureRequired));
+        }
+
+        if self.name.starts_with(HOST_PREFIX) && (self.domain.is_some() || self.path != Some("/")) {
+            return Err(BuildError::new(BuildErrorKind::HostPrefixMismatch));
+        }
+
+        Ok(())
+    }
+
+    fn serialize(&self, expires: Option<&str>) -> String {
+        let mut header = format!("{}={}", self.name, self.value);
+
+        if let Some(expires) = expires {
+            header.push_str("; Expires=");
+            header.push_str(expires);
+        }
+
+        if let Some(max_age) = self.max_age {
+            header.push_str("; Max-Age=");
+            header.push_str(&max_age.as_secs().to_string());
+        }
+
+        if let Some(domain) = self.domain {
+            header.push_str("; Domain=");
+            header.push_str(domain);
+        }
+
+        if let Some(path) = self.path {
+            header.push_str("; Path=");
+            header.push_str(path);
+        }
+
+        if self.secure {
+            header.push_str("; Secure");
+        }
+
+        if self.http_only {
+            header.push_str("; HttpOnly");
+        }
+
+        if let Some(same_site) = self.same_site {
+            header.push_str(match same_site {
+                SameSite::Strict => "; SameSite=Strict",
+                SameSite::Lax => "; SameSite=Lax",
+                SameSite::None => "; SameSite=None",
+            });
+        }
+
+        if self.partitioned {
+            header.push_str("; Partitioned");
+        }
+
+        header
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && is_cookie_name(name)
+}
+
+fn is_valid_domain(domain: &str) -> bool {
+    !domain.is_empty()
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        })
+}
+
+fn is_valid_path(path: &str) -> bool {
+    path.starts_with('/')
+        && path
+            .chars()
+            .all(|c| c.is_ascii() && !c.is_ascii_control() && c != ';')
+}
+
+/// The reason a `Set-Cookie` or a `Cookie` header could not be built.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildErrorKind {
+    /// The cookie name is empty or is not a token.
+    InvalidName,
+    /// The cookie value contains characters other than cookie octets.
+    InvalidValue,
+    /// The `Expires` time is outside of the years 1601 to 9999.
+    InvalidExpires,
+    /// The `Domain` attribute is not a valid domain name.
+    InvalidDomain,
+    /// The `Path` attribute does not start with a `/` or contains control characters or `;`.
+    InvalidPath,
+    /// `SameSite=None`, `Partitioned` or a `__Secure-` or `__Host-` name prefix
+    /// is used without the `Secure` attribute.
+    SecureRequired,
+    /// A `__Host-` prefixed cookie has a `Domain` attribute or a `Path` other than `/`.
+    HostPrefixMismatch,
+    /// The `fmt::Write` a `CookieHeaderWriter` writes into failed.
+    WriteFailed,
+}
+
+impl BuildErrorKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            BuildErrorKind::InvalidName => "invalid cookie name",
+            BuildErrorKind::InvalidValue => "invalid cookie value",
+            BuildErrorKind::InvalidExpires => "expiry date out of range",
+            BuildErrorKind::InvalidDomain => "invalid Domain attribute",
+            BuildErrorKind::InvalidPath => "invalid Path attribute",
+            BuildErrorKind::SecureRequired => "Secure attribute required",
+            BuildErrorKind::HostPrefixMismatch => {
+                "__Host- prefix requires Path=/ and no Domain attribute"
+            }
+            BuildErrorKind::WriteFailed => "failed to write the header",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BuildError {
+    kind: BuildErrorKind,
+}
+
+impl BuildError {
+    pub(crate) fn new(kind: BuildErrorKind) -> BuildError {
+        BuildError { kind }
+    }
+
+    /// Gets the reason the header could not be built.
+    pub fn kind(&self) -> BuildErrorKind {
+        self.kind
+    }
+
+    pub(crate) fn into_error(self) -> Error {
+        Error::BuildError(self)
+    }
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.write_str(BUILD_ERROR_DESCRIPTION)?;
+        f.write_str(": ")?;
+        f.write_str(self.kind.as_str())
+    }
+}
+
+impl std::error::Error for BuildError {
+    fn description(&self) -> &str {
+        BUILD_ERROR_DESCRIPTION
+    }
+
+    fn cause(&self) -> Option<&dyn std::error::Error> {
+        None
+    }
+
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BuildErrorKind, SetCookieBuilder};
+    use crate::{Error, SameSite, SetCookie, SetCookieAttribute};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn build_error_kind(builder: SetCookieBuilder) -> BuildErrorKind {
+        match builder.build() {
+            Err(Error::BuildError(err)) => err.kind(),
+            res => panic!("expected build error, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn name_and_value() {
+        assert_eq!(
+            "name=value",
+            SetCookieBuilder::new("name", "value").build().unwrap()
+        );
+    }
+
+    #[test]
+    fn empty_value() {
+        assert_eq!("name=", SetCookieBuilder::new("name", "").build().unwrap());
+    }
+
+    #[test]
+    fn quoted_value() {
+        assert_eq!(
+            "name=\"a=b\"",
+            SetCookieBuilder::new("name", "\"a=b\"").build().unwrap()
+        );
+    }
+
+    #[test]
+    fn all_attributes() {
+        let header = SetCookieBuilder::new("name", "value")
+            .expires(UNIX_EPOCH + Duration::from_secs(784_111_777))
+            .max_age(Duration::from_millis(60_900))
+            .domain("example.com")
+            .path("/docs")
+            .secure(true)
+            .http_only(true)
+            .same_site(SameSite::None)
+            .partitioned(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            "name=value; Expires=Sun, 06 Nov 1994 08:49:37 GMT; Max-Age=60; \
+             Domain=example.com; Path=/docs; Secure; HttpOnly; SameSite=None; Partitioned",
+            header
+        );
+    }
+
+    #[test]
+    fn parses_back() {
+        let header = SetCookieBuilder::new("name", "value")
+            .domain("example.com")
+            .path("/")
+            .same_site(SameSite::Strict)
+            .build()
+            .unwrap();
+        let set_cookie = SetCookie::parse(&header).unwrap();
+
+        assert_eq!("name", set_cookie.get_name());
+        assert_eq!("value", set_cookie.get_value());
+        assert_eq!(
+            &[
+                SetCookieAttribute::Domain("example.com"),
+                SetCookieAttribute::Path(Some("/")),
+                SetCookieAttribute::SameSite(SameSite::Strict),
+            ],
+            set_cookie.get_attributes()
+        );
+    }
+
+    #[test]
+    fn invalid_name() {
+        assert_eq!(
+            BuildErrorKind::InvalidName,
+            build_error_kind(SetCookieBuilder::new("", "value"))
+        );
+        assert_eq!(
+            BuildErrorKind::InvalidName,
+            build_error_kind(SetCookieBuilder::new("na=me", "value"))
+        );
+        assert_eq!(
+            BuildErrorKind::InvalidName,
+            build_error_kind(SetCookieBuilder::new("na(me)", "value"))
+        );
+        assert_eq!(
+            BuildErrorKind::InvalidName,
+            build_error_kind(SetCookieBuilder::new("na me", "value"))
+        );
+    }
+
+    #[test]
+    fn invalid_value() {
+        for value in &[
+            "a b", "a;b", "a,b", "a\\b", "\"ab", "a\"b", "\"\"", "\u{e9}", "\x7f",
+        ] {
+            assert_eq!(
+                BuildErrorKind::InvalidValue,
+                build_error_kind(SetCookieBuilder::new("name", value))
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_expires() {
+        assert_eq!(
+            BuildErrorKind::InvalidExpires,
+            build_error_kind(
+                SetCookieBuilder::new("name", "value")
+                    .expires(UNIX_EPOCH + Duration::from_secs(253_402_300_800))
+            )
+        );
+    }
+
+    #[test]
+    fn error_message() {
+        let err = SetCookieBuilder::new("name", "a b").build().unwrap_err();
+        assert_eq!(
+            "Cookie Building Error: Build Error: invalid cookie value",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn invalid_domain() {
+        for domain in &[
+            "",
+            ".example.com",
+            "example..com",
+            "-example.com",
+            "exa_mple.com",
+        ] {
+            assert_eq!(
+                BuildErrorKind::InvalidDomain,
+                build_error_kind(SetCookieBuilder::new("name", "value").domain(domain))
+            );
+        }
+    }
+
+    #[cfg(feature = "psl")]
+    #[test]
+    fn public_suffix_domain() {
+        for domain in &["com", "co.uk", "github.io"] {
+            assert_eq!(
+                BuildErrorKind::InvalidDomain,
+                build_error_kind(SetCookieBuilder::new("name", "value").domain(domain))
+            );
+        }
+
+        assert!(SetCookieBuilder::new("name", "value")
+            .domain("example.co.uk")
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn invalid_path() {
+        for path in &["", "docs", "/do;cs", "/do\x09cs"] {
+            assert_eq!(
+                BuildErrorKind::InvalidPath,
+                build_error_kind(SetCookieBuilder::new("name", "value").path(path))
+            );
+        }
+    }
+
+    #[test]
+    fn secure_required() {
+        assert_eq!(
+            BuildErrorKind::SecureRequired,
+            build_error_kind(SetCookieBuilder::new("name", "value").same_site(SameSite::None))
+        );
+        assert_eq!(
+            BuildErrorKind::SecureRequired,
+            build_error_kind(SetCookieBuilder::new("name", "value").partitioned(true))
+        );
+        assert_eq!(
+            BuildErrorKind::SecureRequired,
+            build_error_kind(SetCookieBuilder::new("__Secure-name", "value"))
+        );
+        assert_eq!(
+            "__Secure-name=value; Secure",
+            SetCookieBuilder::new("__Secure-name", "value")
+                .secure(true)
+                .build()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn host_prefix() {
+        assert_eq!(
+            BuildErrorKind::HostPrefixMismatch,
+            build_error_kind(SetCookieBuilder::new("__Host-name", "value").secure(true))
+        );
+        assert_eq!(
+            BuildErrorKind::HostPrefixMismatch,
+            build_error_kind(
+                SetCookieBuilder::new("__Host-name", "value")
+                    .secure(true)
+                    .path("/")
+                    .domain("example.com")
+            )
+        );
+        assert_eq!(
+            "__Host-name=value; Path=/; Secure",
+            SetCookieBuilder::new("__Host-name", "value")
+                .secure(true)
+                .path("/")
+                .build()
+                .unwrap()
+        );
+    }
+}
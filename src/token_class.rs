@@ -0,0 +1,75 @@
+use std::ops::Range;
+
+pub(crate) enum CharTokenClass {
+    None,
+    CookieOctets,
+    TokenOrCookieOctets,
+}
+
+/// Classifies a character as a token character, a cookie-octet that is not a token character,
+/// or neither, following [RFC 6265](https://tools.ietf.org/html/rfc6265.html#section-4.1.1).
+pub(crate) fn char_token_class(c: char) -> CharTokenClass {
+    match c {
+        '\x21'
+        | '\x23'..='\x27'
+        | '\x2a'
+        | '\x2b'
+        | '\x2d'
+        | '\x2e'
+        | '\x30'..='\x39'
+        | '\x41'..='\x5a'
+        | '\x5e'..='\x7a'
+        | '\x7c'
+        | '\x7e' => CharTokenClass::TokenOrCookieOctets,
+        '\x28'
+        | '\x29'
+        | '\x2f'
+        | '\x3a'
+        | '\x3c'
+        | '\x3e'..='\x40'
+        | '\x5b'
+        | '\x5d'
+        | '\x7b'
+        | '\x7d' => CharTokenClass::CookieOctets, // excludes = (x3d)
+        _ => CharTokenClass::None,
+    }
+}
+
+/// Determines whether a string can be the name of a cookie in a cookie string: a token, or empty.
+pub(crate) fn is_cookie_name(name: &str) -> bool {
+    name.chars()
+        .all(|c| matches!(char_token_class(c), CharTokenClass::TokenOrCookieOctets))
+}
+
+/// Determines whether a string is made of cookie-octets and `=`, so that it can be an unquoted
+/// value in a cookie string.
+pub(crate) fn is_cookie_octets(value: &str) -> bool {
+    value
+        .chars()
+        .all(|c| c == '=' || !matches!(char_token_class(c), CharTokenClass::None))
+}
+
+/// Determines whether a string can be the value of a cookie in a cookie string: cookie-octets
+/// and `=`, optionally enclosed in double quotes. Like the strict grammar, this rejects `""`,
+/// although RFC 6265 allows it.
+pub(crate) fn is_cookie_value(value: &str) -> bool {
+    match value.strip_prefix('"') {
+        Some(rest) => match rest.strip_suffix('"') {
+            Some(inner) => !inner.is_empty() && is_cookie_octets(inner),
+            None => false,
+        },
+        None => is_cookie_octets(value),
+    }
+}
+
+/// Determines whether a character is whitespace (space or horizontal tab) in a cookie string.
+pub(crate) fn is_wsp_char(c: char) -> bool {
+    c == '\x20' || c == '\x09'
+}
+
+/// Narrows a range of the input to exclude the surrounding whitespace.
+pub(crate) fn trim_wsp(input: &str, range: Range<usize>) -> Range<usize> {
+    let s = &input[range.clone()];
+    let start = range.start + s.len() - s.trim_start_matches(is_wsp_char).len();
+    start..start + s.trim_matches(is_wsp_char).len()
+}